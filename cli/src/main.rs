@@ -1,13 +1,59 @@
 use clap::{
+    Args,
     Parser,
-    Subcommand
+    Subcommand,
+    ValueEnum
 };
 
+use chrono::{
+    DateTime,
+    Duration as ChronoDuration,
+    NaiveDate,
+    NaiveDateTime,
+    NaiveTime,
+    offset::{
+        Local,
+        TimeZone
+    }
+};
+use futures::StreamExt;
 use itertools::Itertools;
+use std::{
+    fs::File,
+    io::{
+        self,
+        IsTerminal,
+        Write
+    },
+    path::PathBuf,
+    pin::pin,
+    time::Duration
+};
+
+use tokio::sync::mpsc;
 
 use client_lib::{
+    AmbiguousChannel,
+    Channel,
     Device,
+    DeviceResults,
+    DeviceSet,
+    Direction,
+    EntityType,
+    EpisodeScope,
     KeyCode,
+    RecordingConflict,
+    LineupFormat,
+    Recording,
+    RecordingFilter,
+    RecordingResults,
+    RecordingSort,
+    RecordTarget,
+    SeriesRule,
+    StartPosition,
+    Surf,
+    SurfCommand,
+    SurfSource,
     TuningTarget,
     XTVClient,
 };
@@ -16,89 +62,1038 @@ use client_lib::{
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
 struct Cli {
+    #[clap(long, global = true, env = "XTV_DEVICE", value_delimiter = ',')]
+    device: Vec<String>,
+
+    #[clap(long, global = true)]
+    all: bool,
+
     #[clap(subcommand)]
     command: Option<Commands>
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    Channels {},
+    Channels {
+        #[clap(long)]
+        number: Option<u16>,
+
+        #[clap(long)]
+        hd_only: bool,
+
+        #[clap(long, value_enum, default_value_t = ChannelSort::CallSign)]
+        sort: ChannelSort
+    },
     Devices {},
+    Dvr {
+        #[clap(subcommand)]
+        command: DvrCommands
+    },
     Exit {},
+    Export {
+        #[clap(subcommand)]
+        command: ExportCommands
+    },
+    Fav {
+        #[clap(subcommand)]
+        command: FavCommands
+    },
     FF {},
+    Guide {
+        #[clap(long)]
+        channel: Vec<String>,
+
+        #[clap(long)]
+        at: Option<String>,
+
+        #[clap(long, default_value_t = 3)]
+        hours: i64
+    },
+    Macro {
+        #[clap(subcommand)]
+        command: MacroCommands
+    },
+    Move {
+        #[clap(value_parser)]
+        from: String,
+
+        #[clap(value_parser)]
+        to: String,
+
+        #[clap(long)]
+        stop: bool
+    },
+    Now {
+        #[clap(long)]
+        filter: Option<String>,
+
+        #[clap(long)]
+        favorites: bool,
+
+        #[clap(long)]
+        hd_only: bool
+    },
     Pause {},
     Play {},
-    Recordings {},
+    Record {
+        #[clap(value_enum)]
+        target: RecordTarget,
+
+        #[clap(value_parser)]
+        id: String
+    },
+    #[clap(args_conflicts_with_subcommands = true)]
+    Recordings {
+        #[clap(subcommand)]
+        command: Option<RecordingCommands>,
+
+        #[clap(flatten)]
+        filter: RecordingFilterArgs,
+
+        #[clap(long, value_enum, default_value_t = RecordingSort::Date)]
+        sort: RecordingSort,
+
+        #[clap(long, value_enum)]
+        group: Option<RecordingGroup>
+    },
     Rew {},
+    Scene {
+        #[clap(value_parser)]
+        name: String
+    },
+    Scheduled {
+        #[clap(subcommand)]
+        command: Option<ScheduledCommands>
+    },
     Search {
         #[clap(value_parser)]
-        query: String
+        query: String,
+
+        #[clap(long = "type", value_enum, value_delimiter = ',')]
+        types: Vec<EntityType>,
+
+        #[clap(long)]
+        limit: Option<usize>,
+
+        #[clap(long)]
+        json: bool
     },
+    SeriesRules {
+        #[clap(subcommand)]
+        command: Option<SeriesRuleCommands>
+    },
+    Status {},
     Stop {},
+    Surf {
+        #[clap(value_parser)]
+        channels: Vec<String>,
+
+        #[clap(long, conflicts_with_all = ["group", "channels"])]
+        favorites: bool,
+
+        #[clap(long, conflicts_with = "channels")]
+        group: Option<String>,
+
+        #[clap(long, default_value_t = 30, value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64
+    },
     Token {},
     Tune {
         #[clap(value_enum)]
         target: TuningTarget,
 
+        #[clap(value_parser)]
+        id: String,
+
+        #[clap(long)]
+        latest: bool,
+
+        #[clap(long, conflicts_with = "resume")]
+        offset: Option<String>,
+
+        #[clap(long)]
+        resume: bool
+    },
+    Type {
+        #[clap(value_parser)]
+        text: String
+    },
+    Watch {
+        #[clap(long)]
+        json: bool,
+
+        #[clap(long, default_value_t = 5, value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64
+    },
+}
+
+#[derive(Subcommand)]
+enum RecordingCommands {
+    Delete {
+        #[clap(value_parser)]
+        ids: Vec<String>,
+
+        #[clap(flatten)]
+        filter: RecordingFilterArgs,
+
+        #[clap(long)]
+        dry_run: bool,
+
+        #[clap(short, long)]
+        yes: bool
+    },
+    Prune {
+        #[clap(long)]
+        dry_run: bool,
+
+        #[clap(short, long)]
+        yes: bool,
+
+        #[clap(long, conflicts_with = "dry_run", value_parser = clap::value_parser!(u64).range(1..))]
+        every: Option<u64>
+    },
+}
+
+#[derive(Subcommand)]
+enum ScheduledCommands {
+    Cancel {
         #[clap(value_parser)]
         id: String
     },
 }
 
-async fn channels(client: &XTVClient) -> Result<(), Box<dyn std::error::Error>> {
+#[derive(Subcommand)]
+enum SeriesRuleCommands {
+    Create {
+        #[clap(value_parser)]
+        merlin_id: u64,
+
+        #[clap(flatten)]
+        options: SeriesRuleArgs
+    },
+    Delete {
+        #[clap(value_parser)]
+        id: String
+    },
+    List {},
+    Update {
+        #[clap(value_parser)]
+        id: String,
+
+        #[clap(flatten)]
+        options: SeriesRuleArgs
+    },
+}
+
+#[derive(Args)]
+struct SeriesRuleArgs {
+    #[clap(long, value_enum)]
+    episodes: Option<EpisodeScope>,
+
+    #[clap(long)]
+    channel: Option<String>,
+
+    #[clap(long, conflicts_with = "channel")]
+    any_channel: bool,
+
+    #[clap(long)]
+    keep: Option<u32>,
+
+    #[clap(long, conflicts_with = "keep")]
+    keep_all: bool,
+
+    #[clap(long)]
+    start_padding: Option<u32>,
+
+    #[clap(long)]
+    end_padding: Option<u32>
+}
+
+impl SeriesRuleArgs {
+    async fn apply(&self, client: &XTVClient, rule: SeriesRule) -> Result<SeriesRule, Box<dyn std::error::Error>> {
+        let channel = match (&self.channel, self.any_channel) {
+            (Some(channel), _) => Some(client.resolve_channel_number(channel).await?),
+            (None, true) => None,
+            (None, false) => *rule.channel_number()
+        };
+        let keep = match self.keep_all {
+            true => None,
+            false => self.keep.or(*rule.keep_at_most())
+        };
+        let start_padding = self.start_padding.unwrap_or(*rule.start_padding());
+        let end_padding = self.end_padding.unwrap_or(*rule.end_padding());
+        let episodes = self.episodes.unwrap_or(*rule.episodes());
+
+        Ok(
+            rule.with_episodes(episodes)
+                .with_channel(channel)
+                .with_keep_at_most(keep)
+                .with_padding(start_padding, end_padding)
+        )
+    }
+}
+
+#[derive(Args)]
+struct RecordingFilterArgs {
+    #[clap(long)]
+    filter: Option<String>,
+
+    #[clap(long)]
+    series: Option<String>,
+
+    #[clap(long)]
+    since: Option<String>,
+
+    /// Exclusive end time; a bare date includes that whole day
+    #[clap(long)]
+    until: Option<String>,
+
+    #[clap(long)]
+    unwatched: bool
+}
+
+impl RecordingFilterArgs {
+    fn to_filter(&self) -> Result<RecordingFilter, Box<dyn std::error::Error>> {
+        Ok(
+            RecordingFilter::new()
+                .with_title(self.filter.clone())
+                .with_series(self.series.clone())
+                .with_range(self.since.as_deref().map(parse_time).transpose()?, self.until.as_deref().map(parse_until).transpose()?)
+                .with_unwatched(self.unwatched)
+        )
+    }
+}
+
+#[derive(Clone,ValueEnum)]
+enum RecordingGroup {
+    Series
+}
+
+#[derive(Clone,ValueEnum)]
+enum ChannelSort {
+    CallSign,
+    Number
+}
+
+#[derive(Subcommand)]
+enum DvrCommands {
+    Status {},
+}
+
+#[derive(Subcommand)]
+enum ExportCommands {
+    Channels {
+        #[clap(long, value_enum, default_value_t = LineupFormat::M3u)]
+        format: LineupFormat,
+
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+
+        #[clap(long)]
+        url_template: Option<String>
+    },
+    Xmltv {
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+
+        #[clap(long)]
+        channel: Vec<String>,
+
+        #[clap(long, default_value_t = 24)]
+        hours: i64
+    },
+}
+
+#[derive(Subcommand)]
+enum FavCommands {
+    Add {
+        #[clap(value_parser)]
+        channel: String
+    },
+    List {},
+    Next {},
+    Prev {},
+    Remove {
+        #[clap(value_parser)]
+        channel: String
+    },
+}
+
+#[derive(Subcommand)]
+enum MacroCommands {
+    List {},
+    Run {
+        #[clap(value_parser)]
+        name: String
+    },
+}
+
+async fn channels(client: &XTVClient, number: &Option<u16>, hd_only: bool, sort: &ChannelSort) -> Result<(), Box<dyn std::error::Error>> {
     let channel_map = client.channels().await?;
-    for call_sign in channel_map.keys().sorted() {
-        let channels = &channel_map[call_sign];
-        println!("{} ({}): {:?}", call_sign, channels[0].name(), channels.iter().map(|c| c.number()).collect::<Vec<u16>>());
+
+    if let Some(number) = number {
+        let channel = channel_map.by_number(*number).ok_or(format!("Channel not found: {}", number))?;
+        match channel_map.sibling(channel) {
+            Some(sibling) => println!("{} [{}: {}]", channel_line(channel), if sibling.hd() { "HD" } else { "SD" }, sibling.number()),
+            None => println!("{}", channel_line(channel))
+        }
+        return Ok(());
+    }
+
+    match sort {
+        ChannelSort::Number => {
+            channel_map.iter()
+                .filter(|c| !hd_only || c.hd())
+                .for_each(|c| println!("{}", channel_line(c)));
+        },
+        ChannelSort::CallSign => {
+            for call_sign in channel_map.keys().sorted() {
+                let channels = channel_map[call_sign].iter()
+                    .filter(|c| !hd_only || c.hd())
+                    .collect::<Vec<&Channel>>();
+                if let Some(first) = channels.first() {
+                    println!("{} ({}): {:?}", call_sign, first.name(), channels.iter().map(|c| c.number()).collect::<Vec<u16>>());
+                }
+            }
+        }
     }
     Ok(())
 }
 
+fn channel_line(channel: &Channel) -> String {
+    format!("{:>5} {} ({}){}", channel.number(), channel.call_sign(), channel.name(), if channel.hd() { " HD" } else { "" })
+}
+
 async fn devices(client: &XTVClient) -> Result<(), Box<dyn std::error::Error>> {
+    let aliases = client.device_aliases();
     let device_map = client.devices().await?;
-    device_map.values().for_each(|v| println!("{} {}", v.id(), v.name()));
+    device_map.values().for_each(|v| {
+        let names = aliases.iter()
+            .filter(|(_, target)| *target == v.name() || *target == v.id())
+            .map(|(alias, _)| alias.as_str())
+            .sorted()
+            .collect::<Vec<&str>>();
+        match names.is_empty() {
+            true => println!("{} {}", v.id(), v.name()),
+            false => println!("{} {} (aliases: {})", v.id(), v.name(), names.join(", "))
+        }
+    });
     Ok(())
 }
 
-async fn recordings(client: &XTVClient, device: &Device) -> Result<(), Box<dyn std::error::Error>> {
-    let recordings = client.recordings(device).await?;
-    recordings.iter().for_each(|rec| println!("{} {} {}", rec.title(), rec.date_recorded(), rec.media_id()));
+async fn export_channels(client: &XTVClient, format: LineupFormat, output: &Option<PathBuf>, url_template: &Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+    match output {
+        Some(path) => {
+            let mut out = io::BufWriter::new(File::create(path)?);
+            client.export_lineup(&mut out, format, url_template.as_deref()).await?;
+            Ok(out.flush()?)
+        },
+        None => client.export_lineup(&mut io::stdout().lock(), format, url_template.as_deref()).await
+    }
+}
+
+async fn export_xmltv(client: &XTVClient, output: &Option<PathBuf>, channels: &[String], hours: i64) -> Result<(), Box<dyn std::error::Error>> {
+    let from = Local::now();
+    let to = from + ChronoDuration::hours(hours);
+    let numbers = resolve_channels(client, channels).await?;
+
+    match output {
+        Some(path) => {
+            let mut out = io::BufWriter::new(File::create(path)?);
+            client.export_xmltv(&mut out, &from, &to, &numbers).await?;
+            out.flush()?;
+        },
+        None => client.export_xmltv(&mut io::stdout().lock(), &from, &to, &numbers).await?
+    }
+    warn_skipped_listings(client);
     Ok(())
 }
 
-async fn search(client: &XTVClient, query: &String) -> Result<(), Box<dyn std::error::Error>> {
-    let search_results = client.search(query).await?;
-    search_results.iter().for_each(|res| println!("{}: {}", res.name(), res.subtitle()));
+fn warn_skipped_listings(client: &XTVClient) {
+    match client.skipped_listings() {
+        0 => (),
+        n => eprintln!("Warning: skipped {} malformed guide listings", n)
+    }
+}
+
+async fn fav_list(client: &XTVClient) -> Result<(), Box<dyn std::error::Error>> {
+    let favorites = client.favorites();
+    let channel_map = client.channels().await?;
+    for number in favorites.channels() {
+        match channel_map.by_number(*number) {
+            Some(channel) => println!("{}", channel_line(channel)),
+            None => println!("{:>5}", number)
+        }
+    }
     Ok(())
 }
 
+async fn fav_tune(client: &XTVClient, device: &Device, direction: Direction) -> Result<(), Box<dyn std::error::Error>> {
+    let number = client.tune_favorite(device, direction).await?;
+    match client.channels().await?.by_number(number) {
+        Some(channel) => println!("{}", channel_line(channel)),
+        None => println!("{:>5}", number)
+    }
+    Ok(())
+}
+
+async fn guide(client: &XTVClient, channels: &[String], at: &Option<String>, hours: i64) -> Result<(), Box<dyn std::error::Error>> {
+    let from = match at {
+        Some(at) => parse_time(at)?,
+        None => Local::now()
+    };
+    let to = from + ChronoDuration::hours(hours);
+
+    let numbers = resolve_channels(client, channels).await?;
+
+    for airing in client.guide(&from, &to, &numbers).await? {
+        let title = match airing.episode_title() {
+            Some(episode) => format!("{}: {}", airing.title(), episode),
+            None => airing.title().to_string()
+        };
+        println!("{:>5} {:<10} {}-{}  {}  {}  {}", airing.channel_number(), airing.call_sign(), airing.start().format("%a %H:%M"), airing.end().format("%H:%M"), title, airing.flags(), airing.id());
+    }
+    warn_skipped_listings(client);
+    Ok(())
+}
+
+async fn now(client: &XTVClient, filter: &Option<String>, favorites: bool, hd_only: bool) -> Result<(), Box<dyn std::error::Error>> {
+    for row in client.now_playing_grid(favorites, hd_only).await? {
+        if filter.as_ref().is_some_and(|filter| !row.matches(filter)) {
+            continue;
+        }
+        let now = match row.now() {
+            Some(airing) => format!("{} (until {})", airing.title(), airing.end().format("%H:%M")),
+            None => "-".to_string()
+        };
+        let next = match row.next() {
+            Some(airing) => format!("{} {}", airing.start().format("%H:%M"), airing.title()),
+            None => "-".to_string()
+        };
+        println!("{:>5} {:<10} {:<40} {}", row.channel().number(), row.channel().call_sign(), now, next);
+    }
+    warn_skipped_listings(client);
+    Ok(())
+}
+
+async fn resolve_channels(client: &XTVClient, channels: &[String]) -> Result<Vec<u16>, Box<dyn std::error::Error>> {
+    let mut numbers = Vec::new();
+    for channel in channels {
+        numbers.push(client.resolve_channel_number(channel).await?);
+    }
+    Ok(numbers)
+}
+
+fn parse_time(s: &str) -> Result<DateTime<Local>, Box<dyn std::error::Error>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Ok(dt.with_timezone(&Local));
+    }
+
+    let naive = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M")
+        .or_else(|_| NaiveDate::parse_from_str(s, "%Y-%m-%d").map(|d| d.and_time(NaiveTime::default())))
+        .or_else(|_| NaiveTime::parse_from_str(s, "%H:%M").map(|t| Local::now().naive_local().date().and_time(t)))
+        .map_err(|_| format!("Invalid time: {} (expected HH:MM, YYYY-MM-DD, YYYY-MM-DD HH:MM or RFC 3339)", s))?;
+
+    Local.from_local_datetime(&naive)
+        .single()
+        .ok_or(format!("Ambiguous local time: {}", s).into())
+}
+
+fn parse_until(s: &str) -> Result<DateTime<Local>, Box<dyn std::error::Error>> {
+    match NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        Ok(date) => {
+            let next = date.succ_opt().ok_or(format!("Invalid time: {}", s))?;
+            Local.from_local_datetime(&next.and_time(NaiveTime::default()))
+                .single()
+                .ok_or(format!("Ambiguous local time: {}", s).into())
+        },
+        Err(_) => parse_time(s)
+    }
+}
+
+fn macro_list(client: &XTVClient) -> Result<(), Box<dyn std::error::Error>> {
+    let macros = client.macros()?;
+    for name in macros.keys().sorted() {
+        match macros.get(name).unwrap().description() {
+            Some(description) => println!("{}: {}", name, description),
+            None => println!("{}", name)
+        }
+    }
+    Ok(())
+}
+
+async fn macro_run(client: &XTVClient, name: &String, device: &Device) -> Result<(), Box<dyn std::error::Error>> {
+    let macros = client.macros()?;
+    match macros.get(name) {
+        Some(r#macro) => client.run_macro(r#macro, device).await,
+        None => Err(format!("Macro not found: {}", name))?
+    }
+}
+
+async fn recordings(client: &XTVClient, device: &Device, filter: &RecordingFilterArgs, sort: RecordingSort, group: &Option<RecordingGroup>) -> Result<(), Box<dyn std::error::Error>> {
+    let filter = filter.to_filter()?;
+    let mut recordings = client.recordings(device).await?
+        .into_iter()
+        .filter(|rec| filter.matches(rec))
+        .collect::<Vec<Recording>>();
+    sort.sort(&mut recordings);
+
+    match group {
+        Some(RecordingGroup::Series) => {
+            let groups = recordings.iter().into_group_map_by(|rec| rec.series_title().clone());
+            for (series, episodes) in groups.into_iter().sorted_by_key(|(series, _)| series.to_lowercase()) {
+                let total = episodes.iter().map(|rec| *rec.duration()).sum::<u64>();
+                println!("{} ({} recordings, {})", series, episodes.len(), hm(total));
+                episodes.iter().for_each(|rec| println!("  {}", recording_line(rec)));
+            }
+        },
+        None => recordings.iter().for_each(|rec| println!("{}", recording_line(rec)))
+    }
+    Ok(())
+}
+
+async fn recordings_delete(client: &XTVClient, device: &Device, ids: &[String], filter: &RecordingFilterArgs, dry_run: bool, yes: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let filter = filter.to_filter()?.with_ids(ids.to_vec());
+    if filter.is_empty() {
+        Err("Specify recording ids or a filter to delete")?
+    }
+
+    let matching = client.matching_recordings(device, &filter).await?;
+    if matching.is_empty() {
+        println!("No matching recordings");
+        return Ok(());
+    }
+
+    matching.iter().for_each(|rec| println!("{}", recording_line(rec)));
+
+    if dry_run {
+        println!("Would delete {} recordings", matching.len());
+        return Ok(());
+    }
+    if !yes && !confirm(&format!("Delete {} recordings?", matching.len()))? {
+        return Ok(());
+    }
+
+    deleted(&client.delete_recordings(device, matching).await?)
+}
+
+async fn recordings_prune(client: &XTVClient, device: &Device, dry_run: bool, yes: bool, every: Option<u64>) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(minutes) = every {
+        if !yes {
+            Err("Pass --yes to prune on a schedule")?
+        }
+        let mut runs = pin!(client.prune_every(device.clone(), Duration::from_secs(minutes * 60))?);
+        while let Some(results) = runs.next().await {
+            match results {
+                Ok(results) => {
+                    results.iter().filter(|(_, result)| result.is_ok()).for_each(|(rec, _)| println!("{}", recording_line(rec)));
+                    if let Err(e) = deleted(&results) {
+                        eprintln!("{}", e);
+                    }
+                },
+                Err(e) => eprintln!("{}", e)
+            }
+        }
+        return Ok(());
+    }
+
+    let plan = client.prune_plan(device).await?;
+    if plan.is_empty() {
+        println!("Nothing to prune");
+        return Ok(());
+    }
+
+    plan.iter().for_each(|c| println!("{}  [{}]", recording_line(c.recording()), c.policy()));
+
+    if dry_run {
+        println!("Would delete {} recordings", plan.len());
+        return Ok(());
+    }
+    if !yes && !confirm(&format!("Delete {} recordings?", plan.len()))? {
+        return Ok(());
+    }
+
+    deleted(&client.delete_recordings(device, plan.into_iter().map(|c| c.recording().clone()).collect()).await?)
+}
+
+fn deleted(results: &RecordingResults) -> Result<(), Box<dyn std::error::Error>> {
+    let failed = results.iter().filter(|(_, result)| result.is_err()).count();
+    for (rec, result) in results {
+        if let Err(e) = result {
+            eprintln!("{} {}: {}", rec.title(), rec.media_id(), e);
+        }
+    }
+    println!("Deleted {} recordings", results.len() - failed);
+    match failed {
+        0 => Ok(()),
+        n => Err(format!("{} recordings could not be deleted", n))?
+    }
+}
+
+async fn scheduled(client: &XTVClient, device: &Device) -> Result<(), Box<dyn std::error::Error>> {
+    for rec in client.scheduled_recordings(device).await? {
+        let title = match rec.episode_title() {
+            Some(episode_title) => format!("{}: {}", rec.title(), episode_title),
+            None => rec.title().to_string()
+        };
+        println!(
+            "{}-{}  {:<50} {:>8} {}",
+            rec.start_time().format("%a %Y-%m-%d %H:%M"),
+            rec.end_time().format("%H:%M"),
+            title,
+            rec.call_sign().clone().unwrap_or_default(),
+            rec.id()
+        );
+    }
+    Ok(())
+}
+
+async fn dvr_status(client: &XTVClient, device: &Device) -> Result<(), Box<dyn std::error::Error>> {
+    let usage = client.dvr_usage(device).await?;
+    println!(
+        "Used {} of {} ({:.0}%), {} free, {} tuners",
+        human_size(*usage.used()),
+        human_size(*usage.capacity()),
+        usage.percent_used(),
+        human_size(usage.free()),
+        usage.tuner_count()
+    );
+
+    println!();
+    for series in client.series_usage(device).await? {
+        println!("{:<50} {:>4} {:>10} {:>7}", series.series(), series.recordings(), human_size(*series.size()), hm(*series.duration()));
+    }
+
+    let conflicts = RecordingConflict::detect(&client.scheduled_recordings(device).await?, *usage.tuner_count());
+    if !conflicts.is_empty() {
+        println!();
+    }
+    for conflict in conflicts {
+        println!(
+            "Conflict {}-{}: {} recordings on {} tuners: {}",
+            conflict.start().format("%a %Y-%m-%d %H:%M"),
+            conflict.end().format("%H:%M"),
+            conflict.recordings().len(),
+            usage.tuner_count(),
+            conflict.recordings().iter().map(|rec| format!("{} ({})", rec.title(), rec.id())).join(", ")
+        );
+    }
+    Ok(())
+}
+
+async fn series_rules(client: &XTVClient, device: &Device) -> Result<(), Box<dyn std::error::Error>> {
+    for rule in client.series_rules(device).await? {
+        let channel = rule.channel_number().map_or("any".to_string(), |n| n.to_string());
+        let keep = rule.keep_at_most().map_or("all".to_string(), |n| n.to_string());
+        println!(
+            "{:<12} {:<40} {:<3} channel {:<5} keep {:<4} padding -{}/+{} min  {}",
+            rule.id(),
+            rule.title(),
+            rule.episodes(),
+            channel,
+            keep,
+            rule.start_padding(),
+            rule.end_padding(),
+            rule.merlin_id()
+        );
+    }
+    Ok(())
+}
+
+fn confirm(prompt: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    if !io::stdin().is_terminal() {
+        Err("Not a terminal; pass --yes to confirm")?
+    }
+    eprint!("{} [y/N] ", prompt);
+    io::stderr().flush()?;
+
+    let mut line = String::new();
+    io::stdin().read_line(&mut line)?;
+    Ok(matches!(line.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+fn recording_line(rec: &Recording) -> String {
+    let mut title = rec.title().to_string();
+    if let Some(code) = rec.episode_code() {
+        title = format!("{} {}", title, code);
+    }
+    if let Some(episode_title) = rec.episode_title() {
+        title = format!("{}: {}", title, episode_title);
+    }
+    format!(
+        "{}  {:<50} {:>6} {:>8} {:>9} {} {}",
+        rec.date_recorded().format("%Y-%m-%d %H:%M"),
+        title,
+        hm(*rec.duration()),
+        rec.call_sign().clone().unwrap_or_default(),
+        rec.size().map(human_size).unwrap_or_default(),
+        if *rec.watched() { "W" } else { " " },
+        rec.media_id()
+    )
+}
+
+fn parse_offset(s: &str) -> Result<u64, Box<dyn std::error::Error>> {
+    s.split(':')
+        .try_fold(0, |total, part| Ok(total * 60 + part.parse::<u64>().map_err(|_| format!("Invalid offset: {}; use seconds or h:mm:ss", s))?))
+}
+
+fn hm(seconds: u64) -> String {
+    format!("{}:{:02}", seconds / 3600, seconds / 60 % 60)
+}
+
+fn human_size(bytes: u64) -> String {
+    match bytes {
+        b if b >= 1 << 30 => format!("{:.1} GB", b as f64 / (1u64 << 30) as f64),
+        b if b >= 1 << 20 => format!("{:.1} MB", b as f64 / (1u64 << 20) as f64),
+        b => format!("{} KB", b >> 10)
+    }
+}
+
+async fn scene(client: &XTVClient, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let outcomes = client.run_scene(name).await?;
+    let failed = outcomes.iter().filter(|outcome| outcome.result().is_err()).count();
+    for outcome in &outcomes {
+        match outcome.result() {
+            Ok(_) => println!("{}: {}: ok", outcome.device().name(), outcome.action()),
+            Err(e) => println!("{}: {}: {}", outcome.device().name(), outcome.action(), e)
+        }
+    }
+    match failed {
+        0 => Ok(()),
+        n => Err(format!("{} of {} scene actions failed", n, outcomes.len()))?
+    }
+}
+
+async fn search(client: &XTVClient, query: &str, types: &[EntityType], limit: Option<usize>, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let search_results = client.search_all(query, types, limit).await?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&search_results)?);
+        return Ok(());
+    }
+    for res in &search_results {
+        let entity = res.entity();
+        let details = [
+            Some(entity.entity_type().to_string()),
+            entity.release_year().map(|year| year.to_string()),
+            entity.rating().clone()
+        ].into_iter().flatten().join(", ");
+        println!("{}: {} [{}] ({})", res.name(), res.subtitle(), details, entity.merlin_id());
+    }
+    Ok(())
+}
+
+async fn transfer(client: &XTVClient, from: &str, to: &str, stop: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let (from, to) = (client.lookup_device(from).await?, client.lookup_device(to).await?);
+    if from.id() == to.id() {
+        Err("Source and target are the same device")?
+    }
+    let state = client.transfer(&from, &to, stop).await?;
+    println!("{} -> {}: {}", from.name(), to.name(), state);
+    Ok(())
+}
+
+async fn status(client: &XTVClient, device: &Device) -> Result<(), Box<dyn std::error::Error>> {
+    let state = client.device_state(device).await?;
+    println!("{}: {}", device.name(), state);
+    Ok(())
+}
+
+async fn surf(client: &XTVClient, device: &Device, source: SurfSource, interval: u64) -> Result<(), Box<dyn std::error::Error>> {
+    let channels = client.surf_channels(&source).await?;
+    let lines = {
+        let channel_map = client.channels().await?;
+        channels.iter()
+            .map(|n| (*n, channel_map.by_number(*n).map_or(format!("{:>5}", n), channel_line)))
+            .collect::<std::collections::HashMap<u16, String>>()
+    };
+
+    let (tx, rx) = mpsc::channel(8);
+    std::thread::spawn(move || {
+        for line in io::stdin().lines().map_while(Result::ok) {
+            let command = match line.trim() {
+                "p" | "pause" => SurfCommand::Pause,
+                "r" | "resume" => SurfCommand::Resume,
+                "q" | "quit" => SurfCommand::Stop,
+                _ => SurfCommand::Toggle
+            };
+            if tx.blocking_send(command).is_err() || command == SurfCommand::Stop {
+                return;
+            }
+        }
+    });
+
+    eprintln!("Surfing {} channels every {}s (enter: pause/resume, q: quit)", channels.len(), interval);
+    client.surf(device, Surf::new(channels, Duration::from_secs(interval))?, rx, |n| println!("{}", lines[&n])).await
+}
+
 async fn token(client: &XTVClient) -> Result<(), Box<dyn std::error::Error>> {
     let token = client.token().await?;
     println!("{}", token);
     Ok(())
 }
 
+async fn watch(client: &XTVClient, devices: &DeviceSet, json: bool, interval: u64) -> Result<(), Box<dyn std::error::Error>> {
+    let devices = client.resolve_devices(devices).await?;
+    let mut events = pin!(client.watch(devices, Duration::from_secs(interval))?);
+    while let Some(event) = events.next().await {
+        match event {
+            Ok(event) if json => println!("{}", serde_json::to_string(&event)?),
+            Ok(event) => println!("{}", event),
+            Err(e) => eprintln!("{}", e)
+        }
+    }
+    Ok(())
+}
+
+async fn device_set(client: &XTVClient, cli: &Cli) -> Result<DeviceSet, Box<dyn std::error::Error>> {
+    match (cli.all, cli.device.is_empty(), client.default_device()) {
+        (true, _, _) => Ok(DeviceSet::All),
+        (false, false, _) => Ok(DeviceSet::Devices(cli.device.clone())),
+        (false, true, Some(default)) => Ok(DeviceSet::Devices(vec![default])),
+        (false, true, None) => Err(format!("No device specified; use --device, --all or set default_device. Available devices: {}", client.devices().await?.names().join(", ")))?
+    }
+}
+
+async fn single_device(client: &XTVClient, cli: &Cli) -> Result<Device, Box<dyn std::error::Error>> {
+    if cli.all || cli.device.len() > 1 {
+        Err("This command targets a single device")?
+    }
+    client.select_device(cli.device.first().map(String::as_str)).await
+}
+
+fn report<T>(results: DeviceResults<T>) -> Result<(), Box<dyn std::error::Error>> {
+    let failed = results.iter().filter(|(_, result)| result.is_err()).count();
+    if results.len() > 1 || failed > 0 {
+        for (device, result) in &results {
+            match result {
+                Ok(_) => println!("{}: ok", device.name()),
+                Err(e) => println!("{}: {}", device.name(), e)
+            }
+        }
+    }
+    match failed {
+        0 => Ok(()),
+        n => Err(format!("{} of {} devices failed", n, results.len()))?
+    }
+}
+
+async fn press(client: &XTVClient, cli: &Cli, code: KeyCode) -> Result<(), Box<dyn std::error::Error>> {
+    report(client.press_key_many(code, &device_set(client, cli).await?).await?)
+}
+
+async fn tune(client: &XTVClient, cli: &Cli, target: &TuningTarget, id: &str, latest: bool, offset: &Option<String>, resume: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if *target == TuningTarget::Recording {
+        let start = match (offset, resume) {
+            (Some(offset), _) => StartPosition::Offset(parse_offset(offset)?),
+            (None, true) => StartPosition::Resume,
+            (None, false) => StartPosition::Beginning
+        };
+        let results = client.tune_recording_many(id, latest, start, &device_set(client, cli).await?).await?;
+        results.iter()
+            .filter_map(|(_, result)| result.as_ref().ok())
+            .for_each(|rec| println!("{}", recording_line(rec)));
+        return report(results);
+    }
+    if latest || offset.is_some() || resume {
+        Err("--latest, --offset and --resume only apply to recordings")?
+    }
+
+    let id = match target {
+        TuningTarget::Channel => match client.resolve_channel_number(id).await {
+            Ok(number) => number.to_string(),
+            Err(e) => match e.downcast_ref::<AmbiguousChannel>() {
+                Some(ambiguous) if io::stdin().is_terminal() => choose_channel(ambiguous)?.to_string(),
+                _ => return Err(e)
+            }
+        },
+        _ => id.to_string()
+    };
+    report(client.tune_many(target, &id, &device_set(client, cli).await?).await?)
+}
+
+fn choose_channel(ambiguous: &AmbiguousChannel) -> Result<u16, Box<dyn std::error::Error>> {
+    let candidates = ambiguous.candidates();
+    eprintln!("\"{}\" matches several channels:", ambiguous.query());
+    for (i, channel) in candidates.iter().enumerate() {
+        eprintln!("  {}) {} {}", i + 1, channel, channel.name());
+    }
+    eprint!("Choose a channel [1-{}]: ", candidates.len());
+    io::stderr().flush()?;
+
+    let mut line = String::new();
+    io::stdin().read_line(&mut line)?;
+    match line.trim().parse::<usize>() {
+        Ok(i) if (1..=candidates.len()).contains(&i) => Ok(candidates[i - 1].number()),
+        _ => Err("Invalid choice")?
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let cli = Cli::parse();
-    let mut client = XTVClient::new()?;
-    let device = client.lookup_device("Media Room").await?;
+    let client = XTVClient::new()?;
+    match &cli.command {
+        Some(Commands::Channels { number, hd_only, sort }) => { return channels(&client, number, *hd_only, sort).await; }
+        Some(Commands::Devices {}) => { return devices(&client).await; }
+        Some(Commands::Exit {}) => { return press(&client, &cli, KeyCode::Exit).await; }
+        Some(Commands::Export { command: ExportCommands::Channels { format, output, url_template } }) => { return export_channels(&client, *format, output, url_template).await; }
+        Some(Commands::Export { command: ExportCommands::Xmltv { output, channel, hours } }) => { return export_xmltv(&client, output, channel, *hours).await; }
+        Some(Commands::Fav { command: FavCommands::Add { channel } }) => { println!("{}", channel_line(&client.add_favorite(channel).await?)); return Ok(()); }
+        Some(Commands::Fav { command: FavCommands::List {} }) => { return fav_list(&client).await; }
+        Some(Commands::Fav { command: FavCommands::Remove { channel } }) => { client.remove_favorite(channel).await?; return Ok(()); }
+        Some(Commands::FF {}) => { return press(&client, &cli, KeyCode::FastForward).await; }
+        Some(Commands::Guide { channel, at, hours }) => { return guide(&client, channel, at, *hours).await; }
+        Some(Commands::Macro { command: MacroCommands::List {} }) => { return macro_list(&client); }
+        Some(Commands::Move { from, to, stop }) => { return transfer(&client, from, to, *stop).await; }
+        Some(Commands::Now { filter, favorites, hd_only }) => { return now(&client, filter, *favorites, *hd_only).await; }
+        Some(Commands::Pause {}) => { return press(&client, &cli, KeyCode::Pause).await; }
+        Some(Commands::Play {}) => { return press(&client, &cli, KeyCode::Play).await; }
+        Some(Commands::Rew {}) => { return press(&client, &cli, KeyCode::Rewind).await; }
+        Some(Commands::Scene { name }) => { return scene(&client, name).await; }
+        Some(Commands::Search { query, types, limit, json }) => { return search(&client, query, types, *limit, *json).await; }
+        Some(Commands::Stop {}) => { return press(&client, &cli, KeyCode::Stop).await; }
+        Some(Commands::Token {}) => { return token(&client).await; }
+        Some(Commands::Tune { target, id, latest, offset, resume }) => { return tune(&client, &cli, target, id, *latest, offset, *resume).await; }
+        Some(Commands::Watch { json, interval }) => { return watch(&client, &device_set(&client, &cli).await?, *json, *interval).await; }
+        None => { return Ok(()); }
+        _ => ()
+    };
+
+    let device = single_device(&client, &cli).await?;
 
     match &cli.command {
-        Some(Commands::Channels {}) => { channels(&client).await?; }
-        Some(Commands::Devices {}) => { devices(&client).await?; }
-        Some(Commands::Exit {}) => { client.press_key(KeyCode::Exit, &device).await?; }
-        Some(Commands::FF {}) => { client.press_key(KeyCode::FastForward, &device).await?; }
-        Some(Commands::Pause {}) => { client.press_key(KeyCode::Pause, &device).await?; }
-        Some(Commands::Play {}) => { client.press_key(KeyCode::Play, &device).await?; }
-        Some(Commands::Recordings {}) => { recordings(&client, &device).await?; }
-        Some(Commands::Rew {}) => { client.press_key(KeyCode::Rewind, &device).await?; }
-        Some(Commands::Search { query }) => { search(&client, query).await?; }
-        Some(Commands::Stop {}) => { client.press_key(KeyCode::Stop, &device).await?; }
-        Some(Commands::Token {}) => { token(&mut client).await?; }
-        Some(Commands::Tune { target, id }) => { client.tune(target, id, &device).await?; }
-        None => ()
+        Some(Commands::Dvr { command: DvrCommands::Status {} }) => { dvr_status(&client, &device).await?; }
+        Some(Commands::Fav { command: FavCommands::Next {} }) => { fav_tune(&client, &device, Direction::Next).await?; }
+        Some(Commands::Fav { command: FavCommands::Prev {} }) => { fav_tune(&client, &device, Direction::Previous).await?; }
+        Some(Commands::Macro { command: MacroCommands::Run { name } }) => { macro_run(&client, name, &device).await?; }
+        Some(Commands::Record { target, id }) => { client.schedule_recording(&device, *target, id).await?; }
+        Some(Commands::Recordings { command: Some(RecordingCommands::Delete { ids, filter, dry_run, yes }), .. }) => { recordings_delete(&client, &device, ids, filter, *dry_run, *yes).await?; }
+        Some(Commands::Recordings { command: Some(RecordingCommands::Prune { dry_run, yes, every }), .. }) => { recordings_prune(&client, &device, *dry_run, *yes, *every).await?; }
+        Some(Commands::Recordings { command: None, filter, sort, group }) => { recordings(&client, &device, filter, *sort, group).await?; }
+        Some(Commands::Scheduled { command: Some(ScheduledCommands::Cancel { id }) }) => { client.cancel_recording(&device, id).await?; }
+        Some(Commands::Scheduled { command: None }) => { scheduled(&client, &device).await?; }
+        Some(Commands::SeriesRules { command: Some(SeriesRuleCommands::Create { merlin_id, options }) }) => {
+            let rule = options.apply(&client, SeriesRule::new(*merlin_id)).await?;
+            client.create_series_rule(&device, &rule).await?;
+        }
+        Some(Commands::SeriesRules { command: Some(SeriesRuleCommands::Delete { id }) }) => { client.delete_series_rule(&device, id).await?; }
+        Some(Commands::SeriesRules { command: Some(SeriesRuleCommands::List {}) | None }) => { series_rules(&client, &device).await?; }
+        Some(Commands::SeriesRules { command: Some(SeriesRuleCommands::Update { id, options }) }) => {
+            let rule = options.apply(&client, client.series_rule(&device, id).await?).await?;
+            client.update_series_rule(&device, &rule).await?;
+        }
+        Some(Commands::Status {}) => { status(&client, &device).await?; }
+        Some(Commands::Surf { channels, favorites, group, interval }) => {
+            let source = match (favorites, group) {
+                (false, Some(group)) => SurfSource::Group(group.clone()),
+                (false, None) if !channels.is_empty() => SurfSource::Channels(channels.clone()),
+                _ => SurfSource::Favorites
+            };
+            surf(&client, &device, source, *interval).await?;
+        }
+        Some(Commands::Type { text }) => { client.type_text(&device, text).await?; }
+        _ => ()
     };
 
     Ok(())
@@ -1,7 +1,17 @@
 use std::{
-    ops::Index,
+    error,
+    fmt,
+    ops::{
+        Index,
+        RangeBounds
+    },
     collections::{
+        BTreeMap,
         HashMap,
+        btree_map::{
+            Range,
+            Values
+        },
         hash_map::Keys
     }
 };
@@ -11,7 +21,11 @@ use ::serde::{
     Deserializer,
     Serialize
 };
-use super::utils::FileBacked;
+use super::utils::{
+    edit_distance,
+    normalize,
+    FileBacked
+};
 
 
 #[derive(Clone,Debug,Serialize)]
@@ -37,8 +51,46 @@ impl Channel {
     pub fn call_sign(&self) -> &String {
         &self.call_sign
     }
+
+    pub fn hd(&self) -> bool {
+        self.hd
+    }
+
+    pub fn base_call_sign(&self) -> &str {
+        self.call_sign.strip_suffix("HD").filter(|base| !base.is_empty()).unwrap_or(&self.call_sign)
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({}){}", self.call_sign, self.number, if self.hd { " HD" } else { "" })
+    }
+}
+
+#[derive(Debug)]
+pub struct AmbiguousChannel {
+    query: String,
+    candidates: Vec<Channel>
+}
+
+impl AmbiguousChannel {
+    pub fn query(&self) -> &String {
+        &self.query
+    }
+
+    pub fn candidates(&self) -> &Vec<Channel> {
+        &self.candidates
+    }
+}
+
+impl fmt::Display for AmbiguousChannel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Channel is ambiguous: {}. Candidates: {}", self.query, self.candidates.iter().map(Channel::to_string).collect::<Vec<String>>().join(", "))
+    }
 }
 
+impl error::Error for AmbiguousChannel {}
+
 impl<'de> Deserialize<'de> for Channel {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -56,29 +108,138 @@ impl<'de> Deserialize<'de> for Channel {
     }
 }
 
-#[derive(Clone,Debug,Serialize,Deserialize)]
-pub struct ChannelMap(HashMap<String,Vec<Channel>>);
+#[derive(Clone,Debug,Default,Serialize,Deserialize)]
+#[serde(from = "HashMap<String,Vec<Channel>>", into = "HashMap<String,Vec<Channel>>")]
+pub struct ChannelMap {
+    by_call_sign: HashMap<String,Vec<Channel>>,
+    by_number: BTreeMap<u16,Channel>,
+    by_name: HashMap<String,Vec<u16>>
+}
 
 impl ChannelMap {
     pub fn keys(&self) -> Keys<'_, String, Vec<Channel>> {
-        self.0.keys()
+        self.by_call_sign.keys()
     }
 
     pub fn get(&self, k: &String) -> Option<&Vec<Channel>> {
-        self.0.get(k)
+        self.by_call_sign.get(k)
     }
 
-    pub fn get_mut(&mut self, k: &String) -> Option<&mut Vec<Channel>> {
-        self.0.get_mut(k)
+    pub fn insert(&mut self, k: String, v: Vec<Channel>) -> Option<Vec<Channel>> {
+        let old = self.by_call_sign.insert(k, v.clone());
+        for channel in old.iter().flatten() {
+            self.unindex(channel);
+        }
+        for channel in &v {
+            self.index(channel);
+        }
+        old
+    }
+
+    pub fn push(&mut self, channel: Channel) {
+        self.index(&channel);
+        self.by_call_sign.entry(channel.call_sign.clone()).or_default().push(channel);
     }
 
-    pub fn insert(&mut self, k: String, v: Vec<Channel>) -> Option<Vec<Channel>> {
-        self.0.insert(k, v)
-    }    
-    
     pub fn new() -> ChannelMap {
-        ChannelMap(HashMap::<String, Vec<Channel>>::new())
-    }    
+        ChannelMap::default()
+    }
+
+    pub fn by_number(&self, number: u16) -> Option<&Channel> {
+        self.by_number.get(&number)
+    }
+
+    pub fn by_name(&self, name: &str) -> Vec<&Channel> {
+        self.by_name.get(&normalize(name))
+            .map(|numbers| numbers.iter().filter_map(|n| self.by_number.get(n)).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn iter(&self) -> Values<'_, u16, Channel> {
+        self.by_number.values()
+    }
+
+    pub fn range<R: RangeBounds<u16>>(&self, range: R) -> Range<'_, u16, Channel> {
+        self.by_number.range(range)
+    }
+
+    pub fn sibling(&self, channel: &Channel) -> Option<&Channel> {
+        let base = channel.base_call_sign();
+        [base.to_string(), format!("{}HD", base)].iter()
+            .filter_map(|call_sign| self.by_call_sign.get(call_sign))
+            .flatten()
+            .chain(self.by_name(&channel.name))
+            .find(|c| c.hd != channel.hd && (c.base_call_sign() == base || normalize(&c.name) == normalize(&channel.name)))
+    }
+
+    fn index(&mut self, channel: &Channel) {
+        self.by_number.insert(channel.number, channel.clone());
+        self.by_name.entry(normalize(&channel.name)).or_default().push(channel.number);
+    }
+
+    fn unindex(&mut self, channel: &Channel) {
+        self.by_number.remove(&channel.number);
+        if let Some(numbers) = self.by_name.get_mut(&normalize(&channel.name)) {
+            numbers.retain(|n| *n != channel.number);
+        }
+    }
+
+    pub fn resolve(&self, query: &str, prefer_hd: bool) -> Result<Channel, Box<dyn error::Error>> {
+        if let Ok(number) = query.parse::<u16>() {
+            return self.by_number(number)
+                .cloned()
+                .ok_or(format!("Channel not found: {}", query).into());
+        }
+
+        let upper = query.to_uppercase();
+        let norm = normalize(query);
+
+        let tiers: [&dyn Fn(&Channel) -> bool; 4] = [
+            &|c| *c.call_sign() == upper || c.base_call_sign() == upper,
+            &|c| normalize(c.name()) == norm || normalize(c.call_sign()) == norm,
+            &|c| !norm.is_empty() && (normalize(c.name()).contains(&norm) || normalize(c.call_sign()).starts_with(&norm)),
+            &|c| norm.len() > 3 && (edit_distance(&normalize(c.name()), &norm) <= 2 || edit_distance(&normalize(c.base_call_sign()), &norm) <= 1)
+        ];
+
+        for tier in tiers {
+            let mut groups = HashMap::<&str, Vec<&Channel>>::new();
+            for channel in self.iter().filter(|c| tier(c)) {
+                groups.entry(channel.base_call_sign()).or_default().push(channel);
+            }
+
+            let mut candidates = groups.into_values()
+                .map(|group| Self::preferred(group, prefer_hd).clone())
+                .collect::<Vec<Channel>>();
+            candidates.sort_by_key(Channel::number);
+
+            match candidates.len() {
+                0 => continue,
+                1 => return Ok(candidates.remove(0)),
+                _ => return Err(Box::new(AmbiguousChannel { query: query.to_string(), candidates }))
+            }
+        }
+
+        Err(format!("Channel not found: {}", query))?
+    }
+
+    fn preferred(mut group: Vec<&Channel>, prefer_hd: bool) -> &Channel {
+        group.sort_by_key(|c| (c.hd != prefer_hd, c.number));
+        group[0]
+    }
+}
+
+impl From<HashMap<String,Vec<Channel>>> for ChannelMap {
+    fn from(map: HashMap<String,Vec<Channel>>) -> Self {
+        map.into_values()
+            .flatten()
+            .fold(ChannelMap::new(), |mut map, channel| { map.push(channel); map })
+    }
+}
+
+impl From<ChannelMap> for HashMap<String,Vec<Channel>> {
+    fn from(map: ChannelMap) -> Self {
+        map.by_call_sign
+    }
 }
 
 impl Index<&String> for ChannelMap {
@@ -95,3 +256,56 @@ impl FileBacked for ChannelMap {
         format!("{}/.config/xtv/channels", home_dir().unwrap().display())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel(call_sign: &str, name: &str, number: u16, hd: bool) -> Channel {
+        Channel { name: name.to_string(), number, call_sign: call_sign.to_string(), hd }
+    }
+
+    fn channel_map() -> ChannelMap {
+        let mut map = ChannelMap::new();
+        for channel in [
+            channel("WABC", "ABC", 7, false),
+            channel("WABCHD", "ABC", 1007, true),
+            channel("ESPN", "ESPN", 30, false),
+            channel("ESPNHD", "ESPN", 1030, true),
+            channel("ESPN2", "ESPN 2", 31, false),
+            channel("HBO", "HBO East", 500, false)
+        ] {
+            map.push(channel);
+        }
+        map
+    }
+
+    #[test]
+    fn resolve_by_number() {
+        assert_eq!(channel_map().resolve("30", true).unwrap().call_sign(), "ESPN");
+        assert!(channel_map().resolve("99", true).is_err());
+    }
+
+    #[test]
+    fn resolve_prefers_hd_or_sd() {
+        let map = channel_map();
+        assert_eq!(map.resolve("wabc", true).unwrap().number(), 1007);
+        assert_eq!(map.resolve("wabc", false).unwrap().number(), 7);
+        assert_eq!(map.resolve("ESPN", true).unwrap().number(), 1030);
+        assert_eq!(map.resolve("ESPN", false).unwrap().number(), 30);
+    }
+
+    #[test]
+    fn resolve_by_name_and_fuzzy() {
+        let map = channel_map();
+        assert_eq!(map.resolve("hbo east", true).unwrap().number(), 500);
+        assert_eq!(map.resolve("hbo eats", true).unwrap().number(), 500);
+    }
+
+    #[test]
+    fn resolve_ambiguous() {
+        let err = channel_map().resolve("esp", true).unwrap_err();
+        let ambiguous = err.downcast_ref::<AmbiguousChannel>().unwrap();
+        assert_eq!(ambiguous.candidates().iter().map(Channel::number).collect::<Vec<u16>>(), vec![31, 1030]);
+    }
+}
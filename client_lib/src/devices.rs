@@ -26,7 +26,16 @@ pub struct Device {
     name: String
 }
 
-#[derive(Clone,Debug,Serialize,Deserialize)]
+#[derive(Clone,Debug)]
+pub enum DeviceSet {
+    Devices(Vec<String>),
+    Group(String),
+    All
+}
+
+pub type DeviceResults<T> = Vec<(Device, Result<T, Box<dyn std::error::Error>>)>;
+
+#[derive(Clone,Debug,Default,Serialize,Deserialize)]
 pub struct DeviceMap(HashMap<String,Device>);
 
 impl DeviceMap {
@@ -42,6 +51,39 @@ impl DeviceMap {
         self.0.values()
     }
 
+    pub fn find(&self, query: &str, aliases: &HashMap<String,String>) -> Result<&Device, Box<dyn std::error::Error>> {
+        let query = aliases.iter()
+            .find(|(alias, _)| alias.eq_ignore_ascii_case(query))
+            .map_or(query, |(_, target)| target.as_str());
+
+        if let Some(device) = self.0.get(query).or_else(|| self.0.values().find(|device| device.id == query)) {
+            return Ok(device);
+        }
+
+        let lower = query.to_lowercase();
+
+        if let Some(device) = self.0.values().find(|device| device.name.to_lowercase() == lower) {
+            return Ok(device);
+        }
+
+        let mut candidates = self.0.values()
+            .filter(|device| device.name.to_lowercase().starts_with(&lower))
+            .collect::<Vec<&Device>>();
+        candidates.sort_by(|a, b| a.name.cmp(&b.name));
+
+        match candidates.len() {
+            0 => Err(format!("Device not found: {}. Available devices: {}", query, self.names().join(", ")))?,
+            1 => Ok(candidates[0]),
+            _ => Err(format!("Device name is ambiguous: {}. Candidates: {}", query, candidates.iter().map(|device| device.name.as_str()).collect::<Vec<&str>>().join(", ")))?
+        }
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        let mut names = self.0.keys().map(String::as_str).collect::<Vec<&str>>();
+        names.sort();
+        names
+    }
+
     pub fn new() -> DeviceMap {
         DeviceMap(HashMap::<String, Device>::new())
     }
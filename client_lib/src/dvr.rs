@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+use chrono::{
+    DateTime,
+    offset::Local
+};
+use derive_getters::Getters;
+use ::serde::Deserialize;
+use super::recordings::{
+    Recording,
+    ScheduledRecording
+};
+
+
+#[derive(Clone,Debug,Deserialize,Getters)]
+#[serde(rename_all = "camelCase")]
+pub struct DvrUsage {
+    capacity: u64,
+    used: u64,
+    #[serde(default = "DvrUsage::default_tuner_count")]
+    tuner_count: usize
+}
+
+impl DvrUsage {
+    fn default_tuner_count() -> usize {
+        1
+    }
+
+    pub fn free(&self) -> u64 {
+        self.capacity.saturating_sub(self.used)
+    }
+
+    pub fn percent_used(&self) -> f64 {
+        match self.capacity {
+            0 => 0.0,
+            capacity => self.used as f64 * 100.0 / capacity as f64
+        }
+    }
+}
+
+#[derive(Clone,Debug,Getters)]
+pub struct SeriesUsage {
+    series: String,
+    recordings: usize,
+    size: u64,
+    duration: u64
+}
+
+impl SeriesUsage {
+    pub fn from_recordings(recordings: &[Recording]) -> Vec<SeriesUsage> {
+        let mut by_series = HashMap::<String, SeriesUsage>::new();
+        for rec in recordings {
+            let usage = by_series.entry(rec.series_title().to_lowercase())
+                .or_insert_with(|| SeriesUsage { series: rec.series_title().clone(), recordings: 0, size: 0, duration: 0 });
+            usage.recordings += 1;
+            usage.size += rec.size().unwrap_or(0);
+            usage.duration += rec.duration();
+        }
+
+        let mut usage = by_series.into_values().collect::<Vec<SeriesUsage>>();
+        usage.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.series.cmp(&b.series)));
+        usage
+    }
+}
+
+#[derive(Clone,Debug,Getters)]
+pub struct RecordingConflict {
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    recordings: Vec<ScheduledRecording>
+}
+
+impl RecordingConflict {
+    pub fn detect(scheduled: &[ScheduledRecording], tuners: usize) -> Vec<RecordingConflict> {
+        let mut bounds = scheduled.iter()
+            .flat_map(|rec| [*rec.start_time(), *rec.end_time()])
+            .collect::<Vec<DateTime<Local>>>();
+        bounds.sort();
+        bounds.dedup();
+
+        let mut conflicts = Vec::<RecordingConflict>::new();
+        for window in bounds.windows(2) {
+            let (from, to) = (window[0], window[1]);
+            let active = scheduled.iter()
+                .filter(|rec| *rec.start_time() < to && from < *rec.end_time())
+                .collect::<Vec<&ScheduledRecording>>();
+            if active.len() <= tuners {
+                continue;
+            }
+
+            match conflicts.last_mut() {
+                Some(conflict) if conflict.end == from => {
+                    conflict.end = to;
+                    for rec in active {
+                        if !conflict.recordings.iter().any(|r| r.id() == rec.id()) {
+                            conflict.recordings.push(rec.clone());
+                        }
+                    }
+                },
+                _ => conflicts.push(RecordingConflict { start: from, end: to, recordings: active.into_iter().cloned().collect() })
+            }
+        }
+        conflicts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheduled(id: &str, start: &str, end: &str) -> ScheduledRecording {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "title": id,
+            "startTime": format!("Mon, 5 Oct 2026 {}:00 UTC", start),
+            "endTime": format!("Mon, 5 Oct 2026 {}:00 UTC", end)
+        })).unwrap()
+    }
+
+    fn ids(conflict: &RecordingConflict) -> Vec<&str> {
+        let mut ids = conflict.recordings().iter().map(|rec| rec.id().as_str()).collect::<Vec<&str>>();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn back_to_back_recordings_do_not_conflict() {
+        let recordings = [scheduled("a", "19:00", "20:00"), scheduled("b", "20:00", "21:00")];
+        assert!(RecordingConflict::detect(&recordings, 1).is_empty());
+    }
+
+    #[test]
+    fn overlap_within_tuner_count_does_not_conflict() {
+        let recordings = [scheduled("a", "19:00", "20:00"), scheduled("b", "19:30", "20:30")];
+        assert!(RecordingConflict::detect(&recordings, 2).is_empty());
+    }
+
+    #[test]
+    fn three_overlapping_recordings_on_two_tuners() {
+        let recordings = [
+            scheduled("a", "19:00", "21:00"),
+            scheduled("b", "19:30", "20:30"),
+            scheduled("c", "20:00", "22:00")
+        ];
+        let conflicts = RecordingConflict::detect(&recordings, 2);
+
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(*conflicts[0].start(), *recordings[2].start_time());
+        assert_eq!(*conflicts[0].end(), *recordings[1].end_time());
+        assert_eq!(ids(&conflicts[0]), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn adjacent_conflict_windows_merge() {
+        let recordings = [
+            scheduled("a", "19:00", "21:00"),
+            scheduled("b", "19:00", "20:00"),
+            scheduled("c", "20:00", "21:00")
+        ];
+        let conflicts = RecordingConflict::detect(&recordings, 1);
+
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(*conflicts[0].start(), *recordings[0].start_time());
+        assert_eq!(*conflicts[0].end(), *recordings[0].end_time());
+        assert_eq!(ids(&conflicts[0]), vec!["a", "b", "c"]);
+    }
+}
@@ -0,0 +1,73 @@
+use std::fmt;
+use ::serde::Serialize;
+use super::devices::Device;
+use super::state::{
+    DeviceState,
+    PlaybackState
+};
+
+
+#[derive(Clone,Debug,Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum DeviceEvent {
+    Tuned { device: Device, state: DeviceState },
+    Paused { device: Device, state: DeviceState },
+    Resumed { device: Device, state: DeviceState },
+    Stopped { device: Device, state: DeviceState },
+    PoweredOff { device: Device }
+}
+
+impl DeviceEvent {
+    pub fn diff(device: &Device, prev: Option<&DeviceState>, next: &DeviceState) -> Vec<DeviceEvent> {
+        let prev = match prev {
+            Some(prev) => prev,
+            None => return vec![]
+        };
+
+        if !next.is_on() {
+            return if prev.is_on() { vec![DeviceEvent::PoweredOff { device: device.clone() }] } else { vec![] };
+        }
+
+        let tuned = !prev.is_on()
+            || prev.media_type() != next.media_type()
+            || prev.media_id() != next.media_id()
+            || prev.channel_number() != next.channel_number();
+
+        let (device, state) = (device.clone(), next.clone());
+
+        if tuned {
+            return vec![DeviceEvent::Tuned { device, state }];
+        }
+
+        match (prev.playback(), next.playback()) {
+            (PlaybackState::Playing, PlaybackState::Paused) => vec![DeviceEvent::Paused { device, state }],
+            (PlaybackState::Paused, PlaybackState::Playing) => vec![DeviceEvent::Resumed { device, state }],
+            (PlaybackState::Stopped, PlaybackState::Playing) => vec![DeviceEvent::Resumed { device, state }],
+            (PlaybackState::Playing, PlaybackState::Stopped) => vec![DeviceEvent::Stopped { device, state }],
+            (PlaybackState::Paused, PlaybackState::Stopped) => vec![DeviceEvent::Stopped { device, state }],
+            _ => vec![]
+        }
+    }
+
+    pub fn device(&self) -> &Device {
+        match self {
+            DeviceEvent::Tuned { device, .. } => device,
+            DeviceEvent::Paused { device, .. } => device,
+            DeviceEvent::Resumed { device, .. } => device,
+            DeviceEvent::Stopped { device, .. } => device,
+            DeviceEvent::PoweredOff { device } => device
+        }
+    }
+}
+
+impl fmt::Display for DeviceEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DeviceEvent::Tuned { device, state } => write!(f, "{}: tuned {}", device.name(), state),
+            DeviceEvent::Paused { device, state } => write!(f, "{}: paused {}", device.name(), state),
+            DeviceEvent::Resumed { device, state } => write!(f, "{}: resumed {}", device.name(), state),
+            DeviceEvent::Stopped { device, state } => write!(f, "{}: stopped {}", device.name(), state),
+            DeviceEvent::PoweredOff { device } => write!(f, "{}: powered off", device.name())
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use std::io::{
+    self,
+    Write
+};
+use clap::ValueEnum;
+use ::serde::Serialize;
+use crate::channels::Channel;
+use super::channel_id;
+
+
+pub const DEFAULT_URL_TEMPLATE: &str = "xtv://tune/channel/{number}";
+
+#[derive(Serialize)]
+struct LineupEntry<'a> {
+    number: u16,
+    call_sign: &'a str,
+    name: &'a str,
+    hd: bool
+}
+
+#[derive(Clone,Copy,Debug,PartialEq,ValueEnum)]
+pub enum LineupFormat {
+    M3u,
+    Csv,
+    Json
+}
+
+pub fn write<W: Write>(out: &mut W, channels: &[&Channel], format: LineupFormat, url_template: &str) -> io::Result<()> {
+    match format {
+        LineupFormat::M3u => write_m3u(out, channels, url_template),
+        LineupFormat::Csv => write_csv(out, channels),
+        LineupFormat::Json => write_json(out, channels)
+    }
+}
+
+fn write_m3u<W: Write>(out: &mut W, channels: &[&Channel], url_template: &str) -> io::Result<()> {
+    writeln!(out, "#EXTM3U")?;
+    for channel in channels {
+        writeln!(
+            out,
+            r#"#EXTINF:-1 tvg-id="{}" tvg-name="{}" tvg-chno="{}" channel-number="{}",{}"#,
+            channel_id(channel.number(), channel.call_sign()),
+            m3u_field(channel.call_sign()).replace('"', "'"),
+            channel.number(),
+            channel.number(),
+            m3u_field(channel.name())
+        )?;
+        writeln!(out, "{}", url_template.replace("{number}", &channel.number().to_string()).replace("{call_sign}", channel.call_sign()))?;
+    }
+    Ok(())
+}
+
+fn write_csv<W: Write>(out: &mut W, channels: &[&Channel]) -> io::Result<()> {
+    writeln!(out, "number,call_sign,name,hd")?;
+    for channel in channels {
+        writeln!(out, "{},{},{},{}", channel.number(), csv_field(channel.call_sign()), csv_field(channel.name()), channel.hd())?;
+    }
+    Ok(())
+}
+
+fn write_json<W: Write>(out: &mut W, channels: &[&Channel]) -> io::Result<()> {
+    let lineup = channels.iter()
+        .map(|channel| LineupEntry {
+            number: channel.number(),
+            call_sign: channel.call_sign(),
+            name: channel.name(),
+            hd: channel.hd()
+        })
+        .collect::<Vec<LineupEntry>>();
+
+    serde_json::to_writer_pretty(&mut *out, &lineup)?;
+    writeln!(out)
+}
+
+fn csv_field(s: &str) -> String {
+    match s.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", s.replace('"', "\"\"")),
+        false => s.to_string()
+    }
+}
+
+fn m3u_field(s: &str) -> String {
+    s.split(['\r', '\n'])
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel() -> Channel {
+        serde_json::from_value(serde_json::json!({
+            "callSign": "wxyz",
+            "callSignVoiceOverHint": "News, \"Live\"\r\nEast",
+            "number": 12,
+            "isHD": true
+        })).unwrap()
+    }
+
+    fn output(format: LineupFormat) -> String {
+        let channel = channel();
+        let mut out = Vec::new();
+        write(&mut out, &[&channel], format, DEFAULT_URL_TEMPLATE).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn csv_quotes_special_characters() {
+        assert_eq!(output(LineupFormat::Csv), "number,call_sign,name,hd\n12,WXYZ,\"News, \"\"Live\"\"\r\nEast\",true\n");
+        assert_eq!(csv_field("News\rEast"), "\"News\rEast\"");
+    }
+
+    #[test]
+    fn m3u_strips_line_breaks() {
+        let m3u = output(LineupFormat::M3u);
+        let lines = m3u.lines().collect::<Vec<&str>>();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1], r#"#EXTINF:-1 tvg-id="12.WXYZ.xtv" tvg-name="WXYZ" tvg-chno="12" channel-number="12",News, "Live" East"#);
+        assert_eq!(lines[2], "xtv://tune/channel/12");
+    }
+}
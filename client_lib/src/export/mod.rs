@@ -0,0 +1,22 @@
+pub mod lineup;
+pub mod xmltv;
+
+
+pub fn escape_xml(s: &str) -> String {
+    s.chars()
+        .fold(String::with_capacity(s.len()), |mut escaped, c| {
+            match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&apos;"),
+                _ => escaped.push(c)
+            }
+            escaped
+        })
+}
+
+pub fn channel_id(number: u16, call_sign: &str) -> String {
+    format!("{}.{}.xtv", number, call_sign)
+}
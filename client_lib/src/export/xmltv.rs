@@ -0,0 +1,59 @@
+use std::io::{
+    self,
+    Write
+};
+use chrono::{
+    DateTime,
+    offset::{
+        Local,
+        Utc
+    }
+};
+use crate::channels::Channel;
+use crate::guide::Airing;
+use super::{
+    channel_id,
+    escape_xml
+};
+
+
+pub fn write<W: Write>(out: &mut W, channels: &[&Channel], airings: &[Airing]) -> io::Result<()> {
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(out, r#"<!DOCTYPE tv SYSTEM "xmltv.dtd">"#)?;
+    writeln!(out, r#"<tv generator-info-name="xtv">"#)?;
+
+    for channel in channels {
+        writeln!(out, r#"  <channel id="{}">"#, escape_xml(&channel_id(channel.number(), channel.call_sign())))?;
+        writeln!(out, "    <display-name>{}</display-name>", escape_xml(channel.name()))?;
+        writeln!(out, "    <display-name>{}</display-name>", escape_xml(channel.call_sign()))?;
+        writeln!(out, "    <display-name>{}</display-name>", channel.number())?;
+        writeln!(out, "    <display-name>{} {}{}</display-name>", channel.number(), escape_xml(channel.call_sign()), if channel.hd() { " HD" } else { "" })?;
+        writeln!(out, "  </channel>")?;
+    }
+
+    for airing in airings {
+        writeln!(out, r#"  <programme start="{}" stop="{}" channel="{}">"#, timestamp(airing.start()), timestamp(airing.end()), escape_xml(&channel_id(*airing.channel_number(), airing.call_sign())))?;
+        writeln!(out, "    <title>{}</title>", escape_xml(airing.title()))?;
+        if let Some(episode_title) = airing.episode_title() {
+            writeln!(out, "    <sub-title>{}</sub-title>", escape_xml(episode_title))?;
+        }
+        for genre in airing.genres() {
+            writeln!(out, "    <category>{}</category>", escape_xml(genre))?;
+        }
+        if *airing.hd() {
+            writeln!(out, "    <video>")?;
+            writeln!(out, "      <quality>HDTV</quality>")?;
+            writeln!(out, "    </video>")?;
+        }
+        if *airing.new() {
+            writeln!(out, "    <new />")?;
+        }
+        writeln!(out, "  </programme>")?;
+    }
+
+    writeln!(out, "</tv>")
+}
+
+fn timestamp(dt: &DateTime<Local>) -> String {
+    dt.with_timezone(&Utc).format("%Y%m%d%H%M%S +0000").to_string()
+}
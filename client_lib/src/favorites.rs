@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+use home::home_dir;
+use ::serde::{
+    Deserialize,
+    Serialize
+};
+use super::utils::FileBacked;
+
+
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum Direction {
+    Next,
+    Previous
+}
+
+#[derive(Clone,Debug,Default,Deserialize,Serialize)]
+pub struct Favorites {
+    channels: Vec<u16>,
+    #[serde(default)]
+    last_tuned: HashMap<String,u16>
+}
+
+impl Favorites {
+    pub fn channels(&self) -> &Vec<u16> {
+        &self.channels
+    }
+
+    pub fn contains(&self, number: u16) -> bool {
+        self.channels.contains(&number)
+    }
+
+    pub fn add(&mut self, number: u16) -> bool {
+        match self.channels.binary_search(&number) {
+            Ok(_) => false,
+            Err(i) => { self.channels.insert(i, number); true }
+        }
+    }
+
+    pub fn remove(&mut self, number: u16) -> bool {
+        match self.channels.binary_search(&number) {
+            Ok(i) => { self.channels.remove(i); true }
+            Err(_) => false
+        }
+    }
+
+    pub fn last_tuned(&self, device_id: &str) -> Option<u16> {
+        self.last_tuned.get(device_id).copied()
+    }
+
+    pub fn set_last_tuned(&mut self, device_id: &str, number: u16) {
+        self.last_tuned.insert(device_id.to_string(), number);
+    }
+
+    pub fn adjacent(&self, device_id: &str, direction: Direction) -> Option<u16> {
+        let last = self.last_tuned(device_id);
+        match direction {
+            Direction::Next => last
+                .and_then(|last| self.channels.iter().find(|n| **n > last))
+                .or(self.channels.first()),
+            Direction::Previous => last
+                .and_then(|last| self.channels.iter().rev().find(|n| **n < last))
+                .or(self.channels.last())
+        }.copied()
+    }
+
+    pub fn new() -> Favorites {
+        Favorites::default()
+    }
+}
+
+impl FileBacked for Favorites {
+    fn path() -> String {
+        format!("{}/.config/xtv/favorites", home_dir().unwrap().display())
+    }
+}
@@ -0,0 +1,104 @@
+use chrono::{
+    DateTime,
+    offset::Local
+};
+use derive_getters::Getters;
+use super::channels::Channel;
+use ::serde::{
+    de::Error,
+    Deserialize,
+    Deserializer,
+    Serialize
+};
+
+
+#[derive(Clone,Debug,Getters,Serialize)]
+pub struct Airing {
+    id: String,
+    title: String,
+    episode_title: Option<String>,
+    merlin_id: Option<u64>,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    channel_number: u16,
+    call_sign: String,
+    genres: Vec<String>,
+    new: bool,
+    live: bool,
+    hd: bool
+}
+
+impl Airing {
+    pub fn is_on_at(&self, at: &DateTime<Local>) -> bool {
+        self.start <= *at && *at < self.end
+    }
+
+    pub fn overlaps(&self, from: &DateTime<Local>, to: &DateTime<Local>) -> bool {
+        self.start < *to && *from < self.end
+    }
+
+    pub fn flags(&self) -> String {
+        [(self.new, "NEW"), (self.live, "LIVE"), (self.hd, "HD")].iter()
+            .filter(|(set, _)| *set)
+            .map(|(_, flag)| *flag)
+            .collect::<Vec<&str>>()
+            .join(" ")
+    }
+}
+
+impl<'de> Deserialize<'de> for Airing {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value: serde_json::Value = Deserialize::deserialize(deserializer)?;
+        let program = &value["_embedded"]["program"];
+        let channel = &value["_embedded"]["channel"];
+
+        Ok(
+            Self {
+                id: value["id"].as_str().ok_or(D::Error::missing_field("id"))?.to_string(),
+                title: program["title"].as_str().ok_or(D::Error::missing_field("title"))?.to_string(),
+                episode_title: program["episodeTitle"].as_str().map(str::to_string),
+                merlin_id: program["merlinId"].as_u64(),
+                start: super::serde::chrono_rfc2822::deserialize(value["startTime"].clone()).map_err(D::Error::custom)?,
+                end: super::serde::chrono_rfc2822::deserialize(value["endTime"].clone()).map_err(D::Error::custom)?,
+                channel_number: channel["number"].as_u64().ok_or(D::Error::missing_field("number"))? as u16,
+                call_sign: channel["callSign"].as_str().ok_or(D::Error::missing_field("callSign"))?.to_uppercase(),
+                genres: program["genres"].as_array()
+                    .map(|genres| genres.iter().filter_map(|g| g.as_str().map(str::to_string)).collect())
+                    .unwrap_or_default(),
+                new: value["isNew"].as_bool().unwrap_or(false),
+                live: value["isLive"].as_bool().unwrap_or(false),
+                hd: value["isHD"].as_bool().unwrap_or(false)
+            }
+        )
+    }
+}
+
+#[derive(Clone,Debug,Getters,Serialize)]
+pub struct NowNext {
+    channel: Channel,
+    now: Option<Airing>,
+    next: Option<Airing>
+}
+
+impl NowNext {
+    pub fn new(channel: Channel, airings: &[&Airing], at: &DateTime<Local>) -> NowNext {
+        let now = airings.iter().find(|a| a.is_on_at(at)).map(|a| (*a).clone());
+        let after = now.as_ref().map_or(*at, |now| now.end);
+        let next = airings.iter()
+            .filter(|a| a.start >= after)
+            .min_by_key(|a| a.start)
+            .map(|a| (*a).clone());
+
+        NowNext { channel, now, next }
+    }
+
+    pub fn matches(&self, filter: &str) -> bool {
+        let filter = filter.to_lowercase();
+        [&self.now, &self.next].into_iter()
+            .flatten()
+            .any(|a| a.title.to_lowercase().contains(&filter) || a.genres.iter().any(|g| g.to_lowercase().contains(&filter)))
+    }
+}
@@ -0,0 +1,76 @@
+use derive_getters::Getters;
+use ::serde::{
+    Deserialize,
+    Serialize
+};
+use super::KeyCode;
+
+
+#[derive(Clone,Debug,Deserialize,Getters,Serialize)]
+#[serde(default)]
+pub struct KeyboardLayout {
+    rows: Vec<String>,
+    start: (usize, usize),
+    delay_ms: u64
+}
+
+impl Default for KeyboardLayout {
+    fn default() -> Self {
+        KeyboardLayout {
+            rows: vec![
+                "abcdef".to_string(),
+                "ghijkl".to_string(),
+                "mnopqr".to_string(),
+                "stuvwx".to_string(),
+                "yz1234".to_string(),
+                "567890".to_string(),
+                " ".to_string()
+            ],
+            start: (0, 0),
+            delay_ms: 250
+        }
+    }
+}
+
+impl KeyboardLayout {
+    pub fn key_sequence(&self, text: &str) -> Result<Vec<KeyCode>, Box<dyn std::error::Error>> {
+        let rows = self.rows.iter()
+            .map(|row| row.chars().collect::<Vec<char>>())
+            .collect::<Vec<Vec<char>>>();
+
+        let (mut row, mut col) = self.start;
+        let mut keys = Vec::new();
+
+        for c in text.to_lowercase().chars() {
+            let (to_row, to_col) = rows.iter()
+                .enumerate()
+                .find_map(|(r, cells)| cells.iter().position(|&cell| cell == c).map(|col| (r, col)))
+                .ok_or(format!("Character '{}' is not on the keyboard", c))?;
+
+            while row != to_row {
+                if row < to_row {
+                    row += 1;
+                    keys.push(KeyCode::Down);
+                } else {
+                    row -= 1;
+                    keys.push(KeyCode::Up);
+                }
+                col = col.min(rows[row].len().saturating_sub(1));
+            }
+
+            while col != to_col {
+                if col < to_col {
+                    col += 1;
+                    keys.push(KeyCode::Right);
+                } else {
+                    col -= 1;
+                    keys.push(KeyCode::Left);
+                }
+            }
+
+            keys.push(KeyCode::Select);
+        }
+
+        Ok(keys)
+    }
+}
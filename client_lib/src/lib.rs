@@ -1,14 +1,34 @@
 mod channels;
 mod devices;
+mod dvr;
+mod events;
+pub mod export;
+mod favorites;
+mod guide;
+mod keyboard;
+mod macros;
 mod oauth2;
 mod recordings;
 mod response;
+mod retention;
+mod rules;
+mod scenes;
 mod search;
 mod serde;
 mod server;
+mod state;
+mod surf;
 mod utils;
 
+use chrono::{
+    DateTime,
+    offset::Local
+};
 use std::{
+    collections::{
+        HashSet,
+        VecDeque
+    },
     rc::Rc,
     cell::{
         Ref,
@@ -16,10 +36,19 @@ use std::{
     },
     fmt,
     collections::HashMap,
+    io::{
+        self,
+        Write
+    },
     ops::Deref,
+    time::Duration,
 };
 
-use channels::ChannelMap;
+pub use channels::{
+    AmbiguousChannel,
+    Channel,
+    ChannelMap
+};
 use clap::ValueEnum;
 use convert_case::{
     Case,
@@ -27,22 +56,98 @@ use convert_case::{
 };
 pub use devices::{
     Device,
-    DeviceMap
+    DeviceMap,
+    DeviceResults,
+    DeviceSet
+};
+pub use events::DeviceEvent;
+pub use dvr::{
+    DvrUsage,
+    RecordingConflict,
+    SeriesUsage
+};
+pub use export::lineup::LineupFormat;
+pub use favorites::{
+    Direction,
+    Favorites
+};
+pub use guide::{
+    Airing,
+    NowNext
+};
+use futures::{
+    future::join_all,
+    stream::{
+        self,
+        Stream
+    }
 };
 use home::home_dir;
+use itertools::Itertools;
+pub use keyboard::KeyboardLayout;
+pub use macros::{
+    Macro,
+    MacroMap,
+    MacroStep
+};
 use self::oauth2::{
     authenticate,
     refresh,
     Token
 };
-use recordings::Recording;
+pub use recordings::{
+    Recording,
+    RecordingFilter,
+    RecordingResults,
+    RecordingSort,
+    RecordTarget,
+    ScheduledRecording,
+    StartPosition
+};
 use reqwest::{
     Method,
     RequestBuilder,
     Response
 };
 use response::XTVResponse;
-use search::SearchResult;
+pub use retention::{
+    PruneCandidate,
+    RetentionPolicy
+};
+pub use rules::{
+    EpisodeScope,
+    SeriesRule
+};
+pub use scenes::{
+    Scene,
+    SceneAction,
+    SceneOutcome,
+    SceneStep
+};
+pub use search::{
+    Entity,
+    EntityType,
+    Image,
+    SearchResult
+};
+pub use state::{
+    DeviceState,
+    PlaybackState
+};
+pub use surf::{
+    Surf,
+    SurfCommand,
+    SurfSource
+};
+use tokio::{
+    select,
+    sync::mpsc::Receiver
+};
+use tokio::time::{
+    interval,
+    sleep,
+    MissedTickBehavior
+};
 use ::serde::{
     Deserialize,
     Serialize
@@ -57,14 +162,43 @@ pub struct XTVClient {
     config: Rc<RefCell<Config>>,
     client: reqwest::Client,
     channel_map: Rc<RefCell<Option<ChannelMap>>>,
-    device_map: Rc<RefCell<Option<DeviceMap>>>
+    device_map: Rc<RefCell<Option<DeviceMap>>>,
+    favorites: Rc<RefCell<Option<Favorites>>>,
+    guide_cache: Rc<RefCell<HashMap<i64, Vec<Airing>>>>,
+    skipped_listings: Rc<RefCell<usize>>
 }
 
+const GUIDE_WINDOW_SECONDS: i64 = 3 * 60 * 60;
+const SEARCH_PAGE_SIZE: usize = 50;
+
 #[derive(Clone,Debug,Deserialize,Serialize)]
 struct Config {
     api_host: String,
+    default_device: Option<String>,
+    #[serde(default = "Config::default_prefer_hd")]
+    prefer_hd: bool,
     oauth: self::oauth2::Config,
-    token: Option<self::oauth2::Token>
+    token: Option<self::oauth2::Token>,
+    #[serde(default)]
+    channel_aliases: HashMap<String, String>,
+    #[serde(default)]
+    channel_groups: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    device_aliases: HashMap<String, String>,
+    #[serde(default)]
+    device_groups: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    keyboard: KeyboardLayout,
+    #[serde(default)]
+    retention: Vec<RetentionPolicy>,
+    #[serde(default)]
+    scenes: HashMap<String, Scene>
+}
+
+impl Config {
+    fn default_prefer_hd() -> bool {
+        true
+    }
 }
 
 impl FileBacked for Config {
@@ -73,7 +207,7 @@ impl FileBacked for Config {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone,Copy,Debug,Deserialize,PartialEq,Serialize)]
 pub enum KeyCode {
     Play,
     Pause,
@@ -81,6 +215,21 @@ pub enum KeyCode {
     FastForward,
     Rewind,
     Exit,
+    Up,
+    Down,
+    Left,
+    Right,
+    Select,
+    Guide,
+    Menu,
+    Info,
+    Last,
+    ChannelUp,
+    ChannelDown,
+    Record,
+    Mute,
+    VolumeUp,
+    VolumeDown,
 }
 
 impl fmt::Display for KeyCode {
@@ -89,7 +238,7 @@ impl fmt::Display for KeyCode {
     }
 }
 
-#[derive(Debug,Clone,Deserialize,ValueEnum,PartialEq)]
+#[derive(Debug,Clone,Deserialize,Serialize,ValueEnum,PartialEq)]
 pub enum TuningTarget {
     Channel,
     Recording,
@@ -108,18 +257,23 @@ impl XTVClient {
             XTVClient {
                 config: Rc::new(RefCell::new(Config::load()?)),
                 client: reqwest::Client::new(),
-                channel_map: Rc::new(RefCell::new(match ChannelMap::load() {
-                    Ok(map) => Some(map),
-                    Err(_) => None
-                })),
-                device_map: Rc::new(RefCell::new(match DeviceMap::load() {
-                    Ok(map) => Some(map),
-                    Err(_) => None
-                }))
+                channel_map: Rc::new(RefCell::new(ChannelMap::load().ok())),
+                device_map: Rc::new(RefCell::new(DeviceMap::load().ok())),
+                favorites: Rc::new(RefCell::new(Self::load_favorites()?)),
+                guide_cache: Rc::new(RefCell::new(HashMap::new())),
+                skipped_listings: Rc::new(RefCell::new(0))
             }
         )
     }
     
+    fn load_favorites() -> Result<Option<Favorites>, Box<dyn std::error::Error>> {
+        match Favorites::load() {
+            Ok(favorites) => Ok(Some(favorites)),
+            Err(e) if e.downcast_ref::<io::Error>().is_some_and(|e| e.kind() == io::ErrorKind::NotFound) => Ok(None),
+            Err(e) => Err(format!("Could not load favorites from {}: {}", Favorites::path(), e))?
+        }
+    }
+
     pub async fn token(&self) -> Result<String, Box<dyn std::error::Error>> {
         self.config.borrow_mut().token = Some(self.get_token().await?);
         match &self.config.borrow().token {
@@ -129,6 +283,10 @@ impl XTVClient {
     }
 
     pub async fn tune(&self, target: &TuningTarget, id: &String, device: &Device) -> Result<Response, Box<dyn std::error::Error>> {
+        self.tune_at(target, id, device, None).await
+    }
+
+    pub async fn tune_at(&self, target: &TuningTarget, id: &String, device: &Device, offset: Option<u64>) -> Result<Response, Box<dyn std::error::Error>> {
         let url = format!("/devices/{}/remote/tune/{}/", device.id(), target.to_string().to_lowercase());
 
         let id_param = match target {
@@ -138,20 +296,250 @@ impl XTVClient {
         };
 
         let id = match target {
-            TuningTarget::Channel => match id.parse::<u16>() {
-                Ok(x) => x.to_string(),
-                Err(_) => {
-                    let channel_map = self.channels().await?;
-                    match channel_map.get(&id.to_uppercase()) {
-                        None => id.to_string(),
-                        Some(v) => v[0].number().to_string()
-                    }
-                }
-            },
+            TuningTarget::Channel => self.resolve_channel_number(id).await?.to_string(),
             _ => id.to_string()
         };
 
-        self.post(url, &HashMap::from([(id_param, &*id.to_string())])).await
+        let offset = offset.map(|offset| offset.to_string());
+        let mut params = HashMap::from([(id_param, id.as_str())]);
+        if let Some(offset) = &offset {
+            params.insert("offset", offset);
+        }
+
+        let res = self.post(url, &params).await?;
+
+        if *target == TuningTarget::Channel && res.status().is_success() {
+            if let (Ok(number), Some(favorites)) = (id.parse::<u16>(), self.favorites.borrow_mut().as_mut()) {
+                favorites.set_last_tuned(device.id(), number);
+            }
+        }
+
+        Ok(res)
+    }
+
+    pub fn favorites(&self) -> Favorites {
+        self.favorites.borrow().clone().unwrap_or_default()
+    }
+
+    pub async fn add_favorite(&self, query: &str) -> Result<Channel, Box<dyn std::error::Error>> {
+        let channel = self.resolve_channel(query).await?;
+        match self.favorites_mut(|favorites| favorites.add(channel.number())) {
+            true => Ok(channel),
+            false => Err(format!("Already a favorite: {}", channel))?
+        }
+    }
+
+    pub async fn remove_favorite(&self, query: &str) -> Result<u16, Box<dyn std::error::Error>> {
+        let number = self.resolve_channel_number(query).await?;
+        match self.favorites_mut(|favorites| favorites.remove(number)) {
+            true => Ok(number),
+            false => Err(format!("Not a favorite: {}", query))?
+        }
+    }
+
+    pub async fn tune_favorite(&self, device: &Device, direction: Direction) -> Result<u16, Box<dyn std::error::Error>> {
+        let number = self.favorites()
+            .adjacent(device.id(), direction)
+            .ok_or("No favorite channels")?;
+
+        self.tune(&TuningTarget::Channel, &number.to_string(), device).await?.error_for_status()?;
+
+        Ok(number)
+    }
+
+    pub async fn surf_channels(&self, source: &SurfSource) -> Result<Vec<u16>, Box<dyn std::error::Error>> {
+        let queries = match source {
+            SurfSource::Favorites => return Ok(self.favorites().channels().clone()),
+            SurfSource::Group(name) => self.config.borrow().channel_groups.iter()
+                .find(|(group, _)| group.eq_ignore_ascii_case(name))
+                .map(|(_, channels)| channels.clone())
+                .ok_or(format!("Channel group not found: {}", name))?,
+            SurfSource::Channels(channels) => channels.clone()
+        };
+
+        let mut numbers = Vec::new();
+        for query in queries {
+            numbers.push(self.resolve_channel_number(&query).await?);
+        }
+        Ok(numbers)
+    }
+
+    pub async fn surf_step(&self, surf: &mut Surf, device: &Device) -> Result<Option<u16>, Box<dyn std::error::Error>> {
+        if !surf.is_due() {
+            return Ok(None);
+        }
+
+        let number = surf.advance();
+        self.tune(&TuningTarget::Channel, &number.to_string(), device).await?.error_for_status()?;
+
+        Ok(Some(number))
+    }
+
+    pub async fn surf<F: FnMut(u16)>(&self, device: &Device, mut surf: Surf, mut control: Receiver<SurfCommand>, mut on_tune: F) -> Result<(), Box<dyn std::error::Error>> {
+        let mut controlled = true;
+        loop {
+            if let Some(number) = self.surf_step(&mut surf, device).await? {
+                on_tune(number);
+            }
+
+            let paused = surf.is_paused();
+            if paused && !controlled {
+                return Ok(());
+            }
+
+            select! {
+                command = control.recv(), if controlled => match command {
+                    Some(SurfCommand::Stop) => return Ok(()),
+                    Some(command) => surf.apply(command),
+                    None => controlled = false
+                },
+                _ = sleep(surf.until_due()), if !paused => {}
+            }
+        }
+    }
+
+    fn favorites_mut<T, F: FnOnce(&mut Favorites) -> T>(&self, f: F) -> T {
+        f(self.favorites.borrow_mut().get_or_insert_with(Favorites::new))
+    }
+
+    pub async fn resolve_channel(&self, query: &str) -> Result<Channel, Box<dyn std::error::Error>> {
+        let query = self.channel_alias(query);
+        let prefer_hd = self.config.borrow().prefer_hd;
+        self.channels().await?.resolve(&query, prefer_hd)
+    }
+
+    pub async fn resolve_channel_number(&self, query: &str) -> Result<u16, Box<dyn std::error::Error>> {
+        match self.channel_alias(query).parse::<u16>() {
+            Ok(number) => Ok(number),
+            Err(_) => Ok(self.resolve_channel(query).await?.number())
+        }
+    }
+
+    fn channel_alias(&self, query: &str) -> String {
+        self.config.borrow().channel_aliases.iter()
+            .find(|(alias, _)| alias.eq_ignore_ascii_case(query))
+            .map_or(query.to_string(), |(_, target)| target.clone())
+    }
+
+    pub async fn tune_many(&self, target: &TuningTarget, id: &String, devices: &DeviceSet) -> Result<DeviceResults<Response>, Box<dyn std::error::Error>> {
+        let devices = self.resolve_devices(devices).await?;
+
+        self.token().await?;
+        if *target == TuningTarget::Channel {
+            self.channels().await?;
+        }
+
+        let results = join_all(devices.iter().map(|device| async move {
+            Ok(self.tune(target, id, device).await?.error_for_status()?)
+        })).await;
+
+        Ok(devices.into_iter().zip(results).collect())
+    }
+
+    pub async fn resolve_recording(&self, device: &Device, query: &str, latest: bool) -> Result<Recording, Box<dyn std::error::Error>> {
+        Recording::resolve(&self.recordings(device).await?, query, latest)
+    }
+
+    pub async fn tune_recording(&self, device: &Device, query: &str, latest: bool, start: StartPosition) -> Result<(Recording, Response), Box<dyn std::error::Error>> {
+        let recording = self.resolve_recording(device, query, latest).await?;
+        let res = self.tune_at(&TuningTarget::Recording, recording.media_id(), device, start.offset(&recording)).await?;
+        Ok((recording, res))
+    }
+
+    pub async fn tune_recording_many(&self, query: &str, latest: bool, start: StartPosition, devices: &DeviceSet) -> Result<DeviceResults<Recording>, Box<dyn std::error::Error>> {
+        let devices = self.resolve_devices(devices).await?;
+
+        self.token().await?;
+
+        let results = join_all(devices.iter().map(|device| async move {
+            let (recording, res) = self.tune_recording(device, query, latest, start).await?;
+            res.error_for_status()?;
+            Ok(recording)
+        })).await;
+
+        Ok(devices.into_iter().zip(results).collect())
+    }
+
+    pub async fn guide(&self, from: &DateTime<Local>, to: &DateTime<Local>, channels: &[u16]) -> Result<Vec<Airing>, Box<dyn std::error::Error>> {
+        let mut window = from.timestamp() - from.timestamp().rem_euclid(GUIDE_WINDOW_SECONDS);
+        let mut seen = HashSet::<String>::new();
+        let mut airings = Vec::<Airing>::new();
+
+        while window < to.timestamp() {
+            if !self.guide_cache.borrow().contains_key(&window) {
+                let (start, end) = (window.to_string(), (window + GUIDE_WINDOW_SECONDS).to_string());
+                let response = self.get("/listings/".to_string(), &HashMap::from([("startTime", &*start), ("endTime", &*end)]))
+                    .await?
+                    .json::<XTVResponse>()
+                    .await?;
+
+                *self.skipped_listings.borrow_mut() += response.skipped_airings();
+                self.guide_cache.borrow_mut().insert(window, response.airings());
+            }
+
+            for airing in &self.guide_cache.borrow()[&window] {
+                if airing.overlaps(from, to)
+                    && (channels.is_empty() || channels.contains(airing.channel_number()))
+                    && seen.insert(airing.id().clone()) {
+                    airings.push(airing.clone());
+                }
+            }
+
+            window += GUIDE_WINDOW_SECONDS;
+        }
+
+        airings.sort_by(|a, b| (a.channel_number(), a.start()).cmp(&(b.channel_number(), b.start())));
+
+        Ok(airings)
+    }
+
+    pub fn skipped_listings(&self) -> usize {
+        *self.skipped_listings.borrow()
+    }
+
+    pub async fn now_playing_grid(&self, favorites_only: bool, hd_only: bool) -> Result<Vec<NowNext>, Box<dyn std::error::Error>> {
+        let favorites = self.favorites();
+        let channels = self.channels().await?
+            .iter()
+            .filter(|c| !hd_only || c.hd())
+            .filter(|c| !favorites_only || favorites.contains(c.number()))
+            .cloned()
+            .collect::<Vec<Channel>>();
+
+        let now = Local::now();
+        let numbers = match favorites_only || hd_only {
+            true => channels.iter().map(Channel::number).collect::<Vec<u16>>(),
+            false => vec![]
+        };
+        let airings = self.guide(&now, &(now + chrono::Duration::hours(4)), &numbers).await?;
+
+        Ok(
+            channels.into_iter()
+                .map(|channel| {
+                    let airings = airings.iter()
+                        .filter(|a| *a.channel_number() == channel.number())
+                        .collect::<Vec<&Airing>>();
+                    NowNext::new(channel, &airings, &now)
+                })
+                .collect()
+        )
+    }
+
+    pub async fn export_xmltv<W: Write>(&self, out: &mut W, from: &DateTime<Local>, to: &DateTime<Local>, channels: &[u16]) -> Result<(), Box<dyn std::error::Error>> {
+        let airings = self.guide(from, to, channels).await?;
+        let channel_map = self.channels().await?;
+        let channels = channel_map.iter()
+            .filter(|c| channels.is_empty() || channels.contains(&c.number()))
+            .collect::<Vec<&Channel>>();
+        export::xmltv::write(out, &channels, &airings)?;
+        Ok(())
+    }
+
+    pub async fn export_lineup<W: Write>(&self, out: &mut W, format: LineupFormat, url_template: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+        let channel_map = self.channels().await?;
+        let channels = channel_map.iter().collect::<Vec<&Channel>>();
+        export::lineup::write(out, &channels, format, url_template.unwrap_or(export::lineup::DEFAULT_URL_TEMPLATE))?;
+        Ok(())
     }
 
     pub async fn recordings(&self, device: &Device) -> Result<Vec<Recording>, Box<dyn std::error::Error>> {
@@ -164,6 +552,242 @@ impl XTVClient {
         )
     }
 
+    pub async fn scheduled_recordings(&self, device: &Device) -> Result<Vec<ScheduledRecording>, Box<dyn std::error::Error>> {
+        let mut scheduled = self.get(format!("/devices/{}/recordings/scheduled/", device.id()), &HashMap::new())
+            .await?
+            .json::<XTVResponse>()
+            .await?
+            .scheduled_recordings();
+
+        scheduled.sort_by_key(|rec| *rec.start_time());
+
+        Ok(scheduled)
+    }
+
+    pub async fn schedule_recording(&self, device: &Device, target: RecordTarget, id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let id_param = match target {
+            RecordTarget::Entity => "entityId",
+            RecordTarget::Airing => "airingId"
+        };
+
+        self.post(format!("/devices/{}/recordings/scheduled/", device.id()), &HashMap::from([(id_param, id)]))
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    pub async fn cancel_recording(&self, device: &Device, id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.delete(format!("/devices/{}/recordings/scheduled/{}/", device.id(), id))
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    pub async fn series_rules(&self, device: &Device) -> Result<Vec<SeriesRule>, Box<dyn std::error::Error>> {
+        Ok(
+            self.get(format!("/devices/{}/recordings/rules/", device.id()), &HashMap::new())
+                .await?
+                .json::<XTVResponse>()
+                .await?
+                .series_rules()
+        )
+    }
+
+    pub async fn series_rule(&self, device: &Device, id: &str) -> Result<SeriesRule, Box<dyn std::error::Error>> {
+        self.series_rules(device).await?
+            .into_iter()
+            .find(|rule| rule.id() == id)
+            .ok_or(format!("Series rule not found: {}", id).into())
+    }
+
+    pub async fn create_series_rule(&self, device: &Device, rule: &SeriesRule) -> Result<(), Box<dyn std::error::Error>> {
+        let params = rule.params();
+        self.post(format!("/devices/{}/recordings/rules/", device.id()), &params.iter().map(|(k, v)| (*k, v.as_str())).collect())
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    pub async fn update_series_rule(&self, device: &Device, rule: &SeriesRule) -> Result<(), Box<dyn std::error::Error>> {
+        let params = rule.params();
+        self.put(format!("/devices/{}/recordings/rules/{}/", device.id(), rule.id()), &params.iter().map(|(k, v)| (*k, v.as_str())).collect())
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    pub async fn delete_series_rule(&self, device: &Device, id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.delete(format!("/devices/{}/recordings/rules/{}/", device.id(), id))
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    pub async fn delete_recording(&self, device: &Device, media_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.delete(format!("/devices/{}/recordings/{}/", device.id(), media_id))
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    pub async fn delete_recordings(&self, device: &Device, recordings: Vec<Recording>) -> Result<RecordingResults, Box<dyn std::error::Error>> {
+        self.token().await?;
+
+        let results = join_all(recordings.iter().map(|rec| self.delete_recording(device, rec.media_id()))).await;
+
+        Ok(recordings.into_iter().zip(results).collect())
+    }
+
+    pub async fn matching_recordings(&self, device: &Device, filter: &RecordingFilter) -> Result<Vec<Recording>, Box<dyn std::error::Error>> {
+        filter.select(self.recordings(device).await?)
+    }
+
+    pub async fn delete_matching(&self, device: &Device, filter: &RecordingFilter) -> Result<RecordingResults, Box<dyn std::error::Error>> {
+        let matching = self.matching_recordings(device, filter).await?;
+        self.delete_recordings(device, matching).await
+    }
+
+    pub async fn dvr_usage(&self, device: &Device) -> Result<DvrUsage, Box<dyn std::error::Error>> {
+        Ok(
+            self.get(format!("/devices/{}/recordings/usage/", device.id()), &HashMap::new())
+                .await?
+                .json::<XTVResponse>()
+                .await?
+                .dvr_usage()
+        )
+    }
+
+    pub async fn series_usage(&self, device: &Device) -> Result<Vec<SeriesUsage>, Box<dyn std::error::Error>> {
+        Ok(SeriesUsage::from_recordings(&self.recordings(device).await?))
+    }
+
+    pub async fn recording_conflicts(&self, device: &Device) -> Result<Vec<RecordingConflict>, Box<dyn std::error::Error>> {
+        let tuners = *self.dvr_usage(device).await?.tuner_count();
+        Ok(RecordingConflict::detect(&self.scheduled_recordings(device).await?, tuners))
+    }
+
+    pub fn retention_policies(&self) -> Vec<RetentionPolicy> {
+        self.config.borrow().retention.clone()
+    }
+
+    pub async fn prune_plan(&self, device: &Device) -> Result<Vec<PruneCandidate>, Box<dyn std::error::Error>> {
+        let policies = self.retention_policies();
+        if policies.is_empty() {
+            Err("No retention policies configured")?
+        }
+
+        Ok(retention::plan(&policies, &self.recordings(device).await?, &Local::now()))
+    }
+
+    pub async fn prune(&self, device: &Device) -> Result<RecordingResults, Box<dyn std::error::Error>> {
+        let plan = self.prune_plan(device).await?;
+        self.delete_recordings(device, plan.into_iter().map(|c| c.recording().clone()).collect()).await
+    }
+
+    pub fn prune_every(&self, device: Device, period: Duration) -> Result<impl Stream<Item = Result<RecordingResults, Box<dyn std::error::Error>>> + '_, Box<dyn std::error::Error>> {
+        if period.is_zero() {
+            Err("Prune period must be greater than zero")?
+        }
+        if self.retention_policies().is_empty() {
+            Err("No retention policies configured")?
+        }
+
+        let mut ticker = interval(period);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        Ok(stream::unfold(ticker, move |mut ticker| {
+            let device = device.clone();
+            async move {
+                ticker.tick().await;
+                Some((self.prune(&device).await, ticker))
+            }
+        }))
+    }
+
+    pub async fn transfer(&self, from: &Device, to: &Device, stop_source: bool) -> Result<DeviceState, Box<dyn std::error::Error>> {
+        let state = self.device_state(from).await?;
+        if !state.is_on() {
+            Err(format!("Nothing is playing on {}", from.name()))?
+        }
+
+        let (target, id, offset) = match state.media_type() {
+            Some(TuningTarget::Channel) => (
+                TuningTarget::Channel,
+                state.channel_number().ok_or(format!("No channel playing on {}", from.name()))?.to_string(),
+                None
+            ),
+            Some(target) => (
+                target.clone(),
+                state.media_id().clone().ok_or(format!("No media playing on {}", from.name()))?,
+                *state.position()
+            ),
+            None => Err(format!("Nothing is playing on {}", from.name()))?
+        };
+
+        self.tune_at(&target, &id, to, offset).await?.error_for_status()?;
+
+        if *state.playback() == PlaybackState::Paused {
+            self.press_key(KeyCode::Pause, to).await?.error_for_status()?;
+        }
+        if stop_source {
+            self.press_key(KeyCode::Stop, from).await?.error_for_status()?;
+        }
+
+        Ok(state)
+    }
+
+    pub async fn device_state(&self, device: &Device) -> Result<DeviceState, Box<dyn std::error::Error>> {
+        Ok(
+            self.get(format!("/devices/{}/status/", device.id()), &HashMap::new())
+                .await?
+                .json::<XTVResponse>()
+                .await?
+                .device_state()
+        )
+    }
+
+    pub fn watch(&self, devices: Vec<Device>, period: Duration) -> Result<impl Stream<Item = Result<DeviceEvent, Box<dyn std::error::Error>>> + '_, Box<dyn std::error::Error>> {
+        if period.is_zero() {
+            Err("Watch period must be greater than zero")?
+        }
+
+        let mut ticker = interval(period);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let states = HashMap::<String, DeviceState>::new();
+        let pending = VecDeque::<Result<DeviceEvent, Box<dyn std::error::Error>>>::new();
+
+        Ok(stream::unfold((ticker, states, pending), move |(mut ticker, mut states, mut pending)| {
+            let devices = devices.clone();
+            async move {
+                loop {
+                    if let Some(event) = pending.pop_front() {
+                        return Some((event, (ticker, states, pending)));
+                    }
+
+                    ticker.tick().await;
+
+                    if let Err(e) = self.token().await {
+                        pending.push_back(Err(e));
+                        continue;
+                    }
+
+                    let polled = join_all(devices.iter().map(|device| self.device_state(device))).await;
+
+                    for (device, result) in devices.iter().zip(polled) {
+                        match result {
+                            Ok(state) => {
+                                pending.extend(DeviceEvent::diff(device, states.get(device.id()), &state).into_iter().map(Ok));
+                                states.insert(device.id().to_string(), state);
+                            },
+                            Err(e) => pending.push_back(Err(e))
+                        }
+                    }
+                }
+            }
+        }))
+    }
+
     pub async fn devices(&self) -> Result<impl Deref<Target = DeviceMap> + '_, Box<dyn std::error::Error>> {
         if self.device_map.borrow().is_none() {        
             let devices = self.get("/devices/".to_string(), &HashMap::new())
@@ -192,13 +816,8 @@ impl XTVClient {
                 .await?
                 .channels();
 
-            let channel_map = channels.iter()
-                .fold(ChannelMap::new(), |mut map, channel| {
-                    match map.get_mut(&channel.call_sign()) {
-                        Some(v) => { v.push(channel.clone()); map },
-                        None => { map.insert(channel.call_sign().clone(), vec![channel.clone()]); map }
-                    }
-                });
+            let channel_map = channels.into_iter()
+                .fold(ChannelMap::new(), |mut map, channel| { map.push(channel); map });
 
             *self.channel_map.borrow_mut() = Some(channel_map);
         }
@@ -214,9 +833,112 @@ impl XTVClient {
         self.post(format!("/devices/{}/remote/processKey/", device.id()), &params).await
     }
 
+    pub async fn press_key_many(&self, code: KeyCode, devices: &DeviceSet) -> Result<DeviceResults<Response>, Box<dyn std::error::Error>> {
+        let devices = self.resolve_devices(devices).await?;
+
+        self.token().await?;
+
+        let results = join_all(devices.iter().map(|device| async move {
+            Ok(self.press_key(code, device).await?.error_for_status()?)
+        })).await;
+
+        Ok(devices.into_iter().zip(results).collect())
+    }
+
+    pub fn macros(&self) -> Result<MacroMap, Box<dyn std::error::Error>> {
+        MacroMap::load()
+    }
+
+    pub async fn run_macro(&self, r#macro: &Macro, device: &Device) -> Result<(), Box<dyn std::error::Error>> {
+        for (i, step) in r#macro.steps().iter().enumerate() {
+            for _ in 0..*step.repeat() {
+                self.press_key(*step.key(), device)
+                    .await
+                    .and_then(|res| Ok(res.error_for_status()?))
+                    .map_err(|e| format!("Step {} ({}) failed: {}", i + 1, step.key(), e))?;
+
+                if !step.delay().is_zero() {
+                    sleep(step.delay()).await;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn type_text(&self, device: &Device, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let r#macro = {
+            let keyboard = &self.config.borrow().keyboard;
+            Macro::new(
+                keyboard.key_sequence(text)?
+                    .into_iter()
+                    .map(|key| MacroStep::new(key, *keyboard.delay_ms(), 1))
+                    .collect()
+            )
+        };
+
+        self.run_macro(&r#macro, device).await
+    }
+
+    pub fn scenes(&self) -> HashMap<String, Scene> {
+        self.config.borrow().scenes.clone()
+    }
+
+    pub async fn run_scene(&self, name: &str) -> Result<Vec<SceneOutcome>, Box<dyn std::error::Error>> {
+        let scene = self.scenes()
+            .remove(name)
+            .ok_or(format!("Scene not found: {}. Available scenes: {}", name, self.scenes().keys().sorted().join(", ")))?;
+
+        let mut plan = Vec::<(Device, Vec<SceneAction>)>::new();
+        for step in scene.steps() {
+            for device in self.resolve_devices(&DeviceSet::Devices(vec![step.device().clone()])).await? {
+                match plan.iter_mut().find(|(d, _)| d.id() == device.id()) {
+                    Some((_, actions)) => actions.push(step.action().clone()),
+                    None => plan.push((device, vec![step.action().clone()]))
+                }
+            }
+        }
+
+        let macros = match scene.steps().iter().any(|step| matches!(step.action(), SceneAction::Macro { .. })) {
+            true => self.macros()?,
+            false => MacroMap::new()
+        };
+
+        self.token().await?;
+        if scene.steps().iter().any(|step| matches!(step.action(), SceneAction::Tune { target: TuningTarget::Channel, .. })) {
+            self.channels().await?;
+        }
+
+        let outcomes = join_all(plan.into_iter().map(|(device, actions)| {
+            let macros = &macros;
+            async move {
+                let mut outcomes = Vec::new();
+                for action in actions {
+                    let result = self.run_scene_action(&action, &device, macros).await;
+                    outcomes.push(SceneOutcome::new(device.clone(), action, result));
+                }
+                outcomes
+            }
+        })).await;
+
+        Ok(outcomes.into_iter().flatten().collect())
+    }
+
+    async fn run_scene_action(&self, action: &SceneAction, device: &Device, macros: &MacroMap) -> Result<(), Box<dyn std::error::Error>> {
+        match action {
+            SceneAction::Tune { target, id } => { self.tune(target, id, device).await?.error_for_status()?; },
+            SceneAction::Key { key } => { self.press_key(*key, device).await?.error_for_status()?; },
+            SceneAction::Macro { name } => match macros.get(name) {
+                Some(r#macro) => self.run_macro(r#macro, device).await?,
+                None => Err(format!("Macro not found: {}", name))?
+            }
+        };
+        Ok(())
+    }
+
     pub async fn search(&self, query: &String) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
         Ok(
-            self.get(format!("/search/term/"), &HashMap::from([("query", &*query.to_string())]))
+            self.get("/search/term/".to_string(), &HashMap::from([("query", &*query.to_string())]))
                 .await?
                 .json::<XTVResponse>()
                 .await?
@@ -224,11 +946,101 @@ impl XTVClient {
         )        
     }
 
-    pub async fn lookup_device(&self, name: &str) -> Result<Device, Box<dyn std::error::Error>> {
-        match self.devices().await?.get(&name.to_string()) {
-            Some(device) => Ok(device.clone()),
-            None => Err("Device not found")?
+    pub async fn search_page(&self, query: &str, offset: usize, limit: usize) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
+        let (offset, limit) = (offset.to_string(), limit.to_string());
+        Ok(
+            self.get("/search/term/".to_string(), &HashMap::from([("query", query), ("offset", &*offset), ("limit", &*limit)]))
+                .await?
+                .json::<XTVResponse>()
+                .await?
+                .search_results()
+        )
+    }
+
+    pub async fn search_all(&self, query: &str, types: &[EntityType], max: Option<usize>) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
+        let mut seen = HashSet::<u64>::new();
+        let mut results = Vec::<SearchResult>::new();
+        let mut offset = 0;
+
+        loop {
+            let page = self.search_page(query, offset, SEARCH_PAGE_SIZE).await?;
+            let count = page.len();
+            let mut fresh = false;
+
+            for result in page {
+                if seen.insert(*result.entity().merlin_id()) {
+                    fresh = true;
+                    if types.is_empty() || types.contains(result.entity().entity_type()) {
+                        results.push(result);
+                    }
+                }
+            }
+
+            if count < SEARCH_PAGE_SIZE || !fresh || max.is_some_and(|max| results.len() >= max) {
+                break;
+            }
+            offset += count;
         }
+
+        if let Some(max) = max {
+            results.truncate(max);
+        }
+        Ok(results)
+    }
+
+    pub fn default_device(&self) -> Option<String> {
+        self.config.borrow().default_device.clone()
+    }
+
+    pub async fn select_device(&self, name: Option<&str>) -> Result<Device, Box<dyn std::error::Error>> {
+        match name.map(str::to_string).or(self.default_device()) {
+            Some(name) => self.lookup_device(&name).await,
+            None => Err(format!("No device specified and no default_device configured. Available devices: {}", self.devices().await?.names().join(", ")))?
+        }
+    }
+
+    pub fn device_groups(&self) -> HashMap<String, Vec<String>> {
+        self.config.borrow().device_groups.clone()
+    }
+
+    pub async fn resolve_devices(&self, devices: &DeviceSet) -> Result<Vec<Device>, Box<dyn std::error::Error>> {
+        let groups = self.device_groups();
+        let group = |name: &str| groups.iter()
+            .find(|(group, _)| group.eq_ignore_ascii_case(name))
+            .map(|(_, members)| members.clone());
+
+        let names = match devices {
+            DeviceSet::All => {
+                let mut all = self.devices().await?.values().cloned().collect::<Vec<Device>>();
+                all.sort_by(|a, b| a.name().cmp(b.name()));
+                return Ok(all);
+            },
+            DeviceSet::Group(name) => group(name).ok_or(format!("Device group not found: {}", name))?,
+            DeviceSet::Devices(names) => names.iter()
+                .flat_map(|name| group(name).unwrap_or(vec![name.clone()]))
+                .collect()
+        };
+
+        let mut resolved = Vec::<Device>::new();
+        for name in names {
+            let device = self.lookup_device(&name).await?;
+            if !resolved.iter().any(|d| d.id() == device.id()) {
+                resolved.push(device);
+            }
+        }
+
+        Ok(resolved)
+    }
+
+    pub fn device_aliases(&self) -> HashMap<String, String> {
+        self.config.borrow().device_aliases.clone()
+    }
+
+    pub async fn lookup_device(&self, name: &str) -> Result<Device, Box<dyn std::error::Error>> {
+        let aliases = self.device_aliases();
+        self.devices().await?
+            .find(name, &aliases)
+            .cloned()
     }
 
     async fn get(&self, endpoint: String, query: &HashMap<&str,&str>) -> Result<Response, Box<dyn std::error::Error>> {
@@ -247,6 +1059,21 @@ impl XTVClient {
             .map_err(Box::<dyn std::error::Error>::from)
     }
 
+    async fn put(&self, endpoint: String, params: &HashMap<&str, &str>) -> Result<Response, Box<dyn std::error::Error>> {
+        self.request(Method::PUT, endpoint).await?
+            .form(params)
+            .send()
+            .await
+            .map_err(Box::<dyn std::error::Error>::from)
+    }
+
+    async fn delete(&self, endpoint: String) -> Result<Response, Box<dyn std::error::Error>> {
+        self.request(Method::DELETE, endpoint).await?
+            .send()
+            .await
+            .map_err(Box::<dyn std::error::Error>::from)
+    }
+
     async fn request(&self, method: Method, endpoint: String) -> Result<RequestBuilder, Box<dyn std::error::Error>> {
         let url = format!("https://{}{}", self.config.borrow().api_host, endpoint);
 
@@ -259,13 +1086,18 @@ impl XTVClient {
     }
 
     async fn get_token(&self) -> Result<Token, Box<dyn std::error::Error>> {
-        match &self.config.borrow().token {
-            Some(token) if !token.is_expired() => Ok(token.clone()),
-            Some(token) => match refresh(&self.config.borrow().oauth, token.refresh().to_string()).await {
+        let (oauth, token) = {
+            let config = self.config.borrow();
+            (config.oauth.clone(), config.token.clone())
+        };
+
+        match token {
+            Some(token) if !token.is_expired() => Ok(token),
+            Some(token) => match refresh(&oauth, token.refresh().to_string()).await {
                 Ok(token) => Ok(token),
-                Err(_) => authenticate(&self.config.borrow().oauth).await
+                Err(_) => authenticate(&oauth).await
             },
-            None => authenticate(&self.config.borrow().oauth).await
+            None => authenticate(&oauth).await
         }
     }    
 
@@ -280,5 +1112,8 @@ impl Drop for XTVClient {
         if let Some(devices) = &*self.device_map.borrow() {
             devices.save().unwrap();
         }
+        if let Some(favorites) = &*self.favorites.borrow() {
+            favorites.save().unwrap();
+        }
     }
 }
@@ -0,0 +1,75 @@
+use std::{
+    collections::{
+        HashMap,
+        hash_map::Keys
+    },
+    time::Duration
+};
+use derive_getters::Getters;
+use home::home_dir;
+use ::serde::{
+    Deserialize,
+    Serialize
+};
+use super::KeyCode;
+use super::utils::FileBacked;
+
+
+#[derive(Clone,Debug,Deserialize,Getters,Serialize)]
+pub struct MacroStep {
+    key: KeyCode,
+    #[serde(default)]
+    delay_ms: u64,
+    #[serde(default = "MacroStep::default_repeat")]
+    repeat: u32
+}
+
+impl MacroStep {
+    pub fn new(key: KeyCode, delay_ms: u64, repeat: u32) -> MacroStep {
+        MacroStep { key, delay_ms, repeat }
+    }
+
+    pub fn delay(&self) -> Duration {
+        Duration::from_millis(self.delay_ms)
+    }
+
+    fn default_repeat() -> u32 {
+        1
+    }
+}
+
+#[derive(Clone,Debug,Deserialize,Getters,Serialize)]
+pub struct Macro {
+    #[serde(default)]
+    description: Option<String>,
+    steps: Vec<MacroStep>
+}
+
+impl Macro {
+    pub fn new(steps: Vec<MacroStep>) -> Macro {
+        Macro { description: None, steps }
+    }
+}
+
+#[derive(Clone,Debug,Default,Deserialize,Serialize)]
+pub struct MacroMap(HashMap<String,Macro>);
+
+impl MacroMap {
+    pub fn get(&self, k: &str) -> Option<&Macro> {
+        self.0.get(k)
+    }
+
+    pub fn keys(&self) -> Keys<'_, String, Macro> {
+        self.0.keys()
+    }
+
+    pub fn new() -> MacroMap {
+        MacroMap(HashMap::<String, Macro>::new())
+    }
+}
+
+impl FileBacked for MacroMap {
+    fn path() -> String {
+        format!("{}/.config/xtv/macros", home_dir().unwrap().display())
+    }
+}
@@ -2,15 +2,372 @@ use chrono::{
     DateTime,
     offset::Local
 };
+use clap::ValueEnum;
 use derive_getters::Getters;
 use ::serde::Deserialize;
+use super::utils::{
+    edit_distance,
+    normalize
+};
 
 
 #[derive(Clone,Debug,Deserialize,Getters)]
 #[serde(rename_all = "camelCase")]
 pub struct Recording {
-    title: String,    
+    title: String,
     #[serde(with = "super::serde::chrono_rfc2822")]
     date_recorded: DateTime<Local>,
     media_id: String,
+    #[serde(default)]
+    duration: u64,
+    #[serde(default)]
+    channel_number: Option<u16>,
+    #[serde(default)]
+    call_sign: Option<String>,
+    #[serde(default, rename = "seriesTitle")]
+    series: Option<String>,
+    #[serde(default, rename = "seasonNumber")]
+    season: Option<u32>,
+    #[serde(default, rename = "episodeNumber")]
+    episode: Option<u32>,
+    #[serde(default)]
+    episode_title: Option<String>,
+    #[serde(default, rename = "isWatched")]
+    watched: bool,
+    #[serde(default)]
+    size: Option<u64>,
+    #[serde(default, rename = "lastPosition")]
+    position: u64,
+}
+
+impl Recording {
+    pub fn series_title(&self) -> &String {
+        self.series.as_ref().unwrap_or(&self.title)
+    }
+
+    pub fn episode_code(&self) -> Option<String> {
+        match (self.season, self.episode) {
+            (Some(season), Some(episode)) => Some(format!("S{:02}E{:02}", season, episode)),
+            (None, Some(episode)) => Some(format!("E{:02}", episode)),
+            _ => None
+        }
+    }
+
+    pub fn resolve(recordings: &[Recording], query: &str, latest: bool) -> Result<Recording, Box<dyn std::error::Error>> {
+        if let Some(rec) = recordings.iter().find(|rec| rec.media_id == query) {
+            return Ok(rec.clone());
+        }
+
+        let (title, code) = match query.rsplit_once(' ').and_then(|(title, code)| Some((title, parse_episode_code(code)?))) {
+            Some((title, code)) => (title, Some(code)),
+            None => (query, None)
+        };
+        let norm = normalize(title);
+
+        let tiers: [&dyn Fn(&Recording) -> bool; 3] = [
+            &|r| normalize(r.series_title()) == norm || normalize(&r.title) == norm,
+            &|r| !norm.is_empty() && (normalize(r.series_title()).contains(&norm)
+                || normalize(&r.title).contains(&norm)
+                || r.episode_title.as_ref().is_some_and(|e| normalize(e).contains(&norm))),
+            &|r| norm.len() > 3 && edit_distance(&normalize(r.series_title()), &norm) <= 2
+        ];
+
+        for tier in tiers {
+            let mut matches = recordings.iter()
+                .filter(|r| tier(r))
+                .filter(|r| code.is_none_or(|(season, episode)| r.episode == Some(episode) && season.is_none_or(|s| r.season == Some(s))))
+                .collect::<Vec<&Recording>>();
+            matches.sort_by_key(|r| std::cmp::Reverse(r.date_recorded));
+
+            let mut series = matches.iter().map(|r| r.series_title().as_str()).collect::<Vec<&str>>();
+            series.sort();
+            series.dedup();
+
+            match (matches.len(), series.len()) {
+                (0, _) => continue,
+                (1, _) => return Ok(matches[0].clone()),
+                (_, 1) if latest => return Ok(matches[0].clone()),
+                (_, 1) => Err(format!(
+                    "Several recordings of {} match; use --latest or name an episode. Candidates: {}",
+                    series[0],
+                    matches.iter().map(|r| match r.episode_code() {
+                        Some(code) => format!("{} ({})", code, r.date_recorded.format("%Y-%m-%d")),
+                        None => r.date_recorded.format("%Y-%m-%d").to_string()
+                    }).collect::<Vec<String>>().join(", ")
+                ))?,
+                _ => Err(format!("Recording is ambiguous: {}. Candidates: {}", query, series.join(", ")))?
+            }
+        }
+
+        Err(format!("Recording not found: {}", query))?
+    }
+}
+
+fn parse_episode_code(code: &str) -> Option<(Option<u32>, u32)> {
+    let code = code.to_uppercase();
+    let (season, episode) = code.strip_prefix('S')
+        .and_then(|rest| rest.split_once('E'))
+        .map(|(season, episode)| (Some(season), episode))
+        .or(code.strip_prefix('E').map(|episode| (None, episode)))?;
+
+    Some((season.map(str::parse).transpose().ok()?, episode.parse().ok()?))
+}
+
+#[derive(Clone,Copy,Debug,Default,PartialEq)]
+pub enum StartPosition {
+    #[default]
+    Beginning,
+    Offset(u64),
+    Resume
+}
+
+impl StartPosition {
+    pub fn offset(&self, recording: &Recording) -> Option<u64> {
+        match self {
+            StartPosition::Beginning => None,
+            StartPosition::Offset(offset) => Some(*offset),
+            StartPosition::Resume => Some(recording.position).filter(|position| *position > 0)
+        }
+    }
+}
+
+#[derive(Clone,Debug,Deserialize,Getters)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledRecording {
+    id: String,
+    title: String,
+    #[serde(default)]
+    episode_title: Option<String>,
+    #[serde(with = "super::serde::chrono_rfc2822")]
+    start_time: DateTime<Local>,
+    #[serde(with = "super::serde::chrono_rfc2822")]
+    end_time: DateTime<Local>,
+    #[serde(default)]
+    channel_number: Option<u16>,
+    #[serde(default)]
+    call_sign: Option<String>,
+    #[serde(default)]
+    merlin_id: Option<u64>,
+}
+
+#[derive(Clone,Copy,Debug,PartialEq,ValueEnum)]
+pub enum RecordTarget {
+    Entity,
+    Airing
+}
+
+pub type RecordingResults = Vec<(Recording, Result<(), Box<dyn std::error::Error>>)>;
+
+#[derive(Clone,Copy,Debug,PartialEq,ValueEnum)]
+pub enum RecordingSort {
+    Date,
+    Title,
+    Duration
+}
+
+impl RecordingSort {
+    pub fn sort(&self, recordings: &mut [Recording]) {
+        match self {
+            RecordingSort::Date => recordings.sort_by_key(|r| r.date_recorded),
+            RecordingSort::Title => recordings.sort_by_key(|r| (r.title.to_lowercase(), r.date_recorded)),
+            RecordingSort::Duration => recordings.sort_by_key(|r| r.duration)
+        }
+    }
+}
+
+#[derive(Clone,Debug,Default)]
+pub struct RecordingFilter {
+    ids: Vec<String>,
+    title: Option<String>,
+    series: Option<String>,
+    from: Option<DateTime<Local>>,
+    to: Option<DateTime<Local>>,
+    unwatched: bool
+}
+
+impl RecordingFilter {
+    pub fn new() -> RecordingFilter {
+        RecordingFilter::default()
+    }
+
+    pub fn with_ids(mut self, ids: Vec<String>) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    pub fn ids(&self) -> &Vec<String> {
+        &self.ids
+    }
+
+    pub fn with_title(mut self, title: Option<String>) -> Self {
+        self.title = title.map(|t| t.to_lowercase());
+        self
+    }
+
+    pub fn with_series(mut self, series: Option<String>) -> Self {
+        self.series = series.map(|s| s.to_lowercase());
+        self
+    }
+
+    pub fn with_range(mut self, from: Option<DateTime<Local>>, to: Option<DateTime<Local>>) -> Self {
+        self.from = from;
+        self.to = to;
+        self
+    }
+
+    pub fn with_unwatched(mut self, unwatched: bool) -> Self {
+        self.unwatched = unwatched;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty() && self.title.is_none() && self.series.is_none() && self.from.is_none() && self.to.is_none() && !self.unwatched
+    }
+
+    pub fn matches(&self, recording: &Recording) -> bool {
+        (self.ids.is_empty() || self.ids.contains(&recording.media_id))
+        && self.title.as_ref().is_none_or(|title| {
+            recording.title.to_lowercase().contains(title)
+                || recording.episode_title.as_ref().is_some_and(|e| e.to_lowercase().contains(title))
+        })
+        && self.series.as_ref().is_none_or(|series| recording.series_title().to_lowercase().contains(series))
+        && self.from.is_none_or(|from| recording.date_recorded >= from)
+        && self.to.is_none_or(|to| recording.date_recorded < to)
+        && !(self.unwatched && recording.watched)
+    }
+
+    pub fn select(&self, recordings: Vec<Recording>) -> Result<Vec<Recording>, Box<dyn std::error::Error>> {
+        if self.is_empty() {
+            Err("Refusing to select recordings with an empty filter")?
+        }
+
+        let matching = recordings.into_iter()
+            .filter(|rec| self.matches(rec))
+            .collect::<Vec<Recording>>();
+
+        if let Some(id) = self.ids.iter().find(|id| !matching.iter().any(|rec| rec.media_id == **id)) {
+            Err(format!("Recording not found or not matching filter: {}", id))?
+        }
+
+        Ok(matching)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recording(id: &str, title: &str, series: Option<&str>, season: u32, episode: u32, date: &str, watched: bool) -> Recording {
+        serde_json::from_value(serde_json::json!({
+            "title": title,
+            "dateRecorded": date,
+            "mediaId": id,
+            "seriesTitle": series,
+            "seasonNumber": season,
+            "episodeNumber": episode,
+            "isWatched": watched
+        })).unwrap()
+    }
+
+    fn recordings() -> Vec<Recording> {
+        vec![
+            recording("1", "Jeopardy!", Some("Jeopardy!"), 40, 1, "Mon, 5 Oct 2026 19:00:00 UTC", true),
+            recording("2", "Jeopardy!", Some("Jeopardy!"), 40, 2, "Tue, 6 Oct 2026 19:00:00 UTC", false),
+            recording("3", "The Office", Some("The Office"), 2, 3, "Tue, 6 Oct 2026 20:00:00 UTC", true),
+            recording("4", "Nightly News", None, 0, 0, "Tue, 1 Sep 2026 20:00:00 UTC", false)
+        ]
+    }
+
+    fn media_ids(recordings: &[Recording]) -> Vec<&str> {
+        recordings.iter().map(|rec| rec.media_id.as_str()).collect()
+    }
+
+    #[test]
+    fn select_by_ids() {
+        let filter = RecordingFilter::new().with_ids(vec!["1".to_string(), "3".to_string()]);
+        assert_eq!(media_ids(&filter.select(recordings()).unwrap()), vec!["1", "3"]);
+    }
+
+    #[test]
+    fn select_by_filter() {
+        let filter = RecordingFilter::new().with_series(Some("jeopardy".to_string())).with_unwatched(true);
+        assert_eq!(media_ids(&filter.select(recordings()).unwrap()), vec!["2"]);
+    }
+
+    #[test]
+    fn select_requires_ids_and_filter_to_match() {
+        let filter = RecordingFilter::new().with_ids(vec!["2".to_string()]).with_unwatched(true);
+        assert_eq!(media_ids(&filter.select(recordings()).unwrap()), vec!["2"]);
+
+        let filter = RecordingFilter::new().with_ids(vec!["1".to_string(), "2".to_string()]).with_unwatched(true);
+        assert!(filter.select(recordings()).is_err());
+    }
+
+    #[test]
+    fn select_rejects_empty_filter() {
+        assert!(RecordingFilter::new().is_empty());
+        assert!(RecordingFilter::new().select(recordings()).is_err());
+    }
+
+    #[test]
+    fn select_rejects_unknown_id() {
+        let filter = RecordingFilter::new().with_ids(vec!["99".to_string()]);
+        assert!(filter.select(recordings()).is_err());
+    }
+
+    fn resolve(recordings: &[Recording], query: &str, latest: bool) -> Result<String, String> {
+        Recording::resolve(recordings, query, latest)
+            .map(|rec| rec.media_id)
+            .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn resolve_by_media_id_and_title() {
+        assert_eq!(resolve(&recordings(), "3", false), Ok("3".to_string()));
+        assert_eq!(resolve(&recordings(), "the office", false), Ok("3".to_string()));
+        assert_eq!(resolve(&recordings(), "news", false), Ok("4".to_string()));
+        assert!(resolve(&recordings(), "seinfeld", true).unwrap_err().starts_with("Recording not found"));
+    }
+
+    #[test]
+    fn resolve_fuzzy() {
+        assert_eq!(resolve(&recordings(), "jeoprdy", true), Ok("2".to_string()));
+        assert_eq!(resolve(&recordings(), "the offce", false), Ok("3".to_string()));
+    }
+
+    #[test]
+    fn resolve_latest_episode() {
+        assert_eq!(resolve(&recordings(), "jeopardy", true), Ok("2".to_string()));
+
+        let err = resolve(&recordings(), "jeopardy", false).unwrap_err();
+        assert!(err.starts_with("Several recordings of Jeopardy! match"));
+        assert!(err.contains("S40E02 (2026-10-06), S40E01 (2026-10-05)"));
+    }
+
+    #[test]
+    fn resolve_episode_code() {
+        assert_eq!(resolve(&recordings(), "jeopardy S40E01", false), Ok("1".to_string()));
+        assert_eq!(resolve(&recordings(), "jeopardy e2", false), Ok("2".to_string()));
+        assert!(resolve(&recordings(), "jeopardy S39E01", false).is_err());
+    }
+
+    #[test]
+    fn resolve_ambiguous_series() {
+        let mut recordings = recordings();
+        recordings.push(recording("5", "Celebrity Jeopardy!", Some("Celebrity Jeopardy!"), 1, 1, "Wed, 7 Oct 2026 20:00:00 UTC", false));
+
+        assert_eq!(resolve(&recordings, "jeopardy", true), Ok("2".to_string()));
+        let err = resolve(&recordings, "jeop", true).unwrap_err();
+        assert_eq!(err, "Recording is ambiguous: jeop. Candidates: Celebrity Jeopardy!, Jeopardy!");
+    }
+
+    #[test]
+    fn episode_codes() {
+        assert_eq!(parse_episode_code("S01E02"), Some((Some(1), 2)));
+        assert_eq!(parse_episode_code("s10e120"), Some((Some(10), 120)));
+        assert_eq!(parse_episode_code("E05"), Some((None, 5)));
+        assert_eq!(parse_episode_code("Show"), None);
+        assert_eq!(parse_episode_code("SxE1"), None);
+        assert_eq!(parse_episode_code("2"), None);
+    }
 }
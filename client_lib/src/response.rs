@@ -1,8 +1,15 @@
 use std::fmt;
 use super::channels::Channel;
 use super::devices::Device;
-use super::recordings::Recording;
+use super::dvr::DvrUsage;
+use super::guide::Airing;
+use super::recordings::{
+    Recording,
+    ScheduledRecording
+};
+use super::rules::SeriesRule;
 use super::search::SearchResult;
+use super::state::DeviceState;
 use ::serde::{
     de::{
         Deserializer,
@@ -16,25 +23,48 @@ use ::serde::{
 
 
 pub enum XTVResponse {
+    Airings(Vec<Airing>, usize),
     Channels(Vec<Channel>),
     Devices(Vec<Device>),
+    DeviceState(DeviceState),
+    DvrUsage(DvrUsage),
     Recordings(Vec<Recording>),
+    ScheduledRecordings(Vec<ScheduledRecording>),
     SearchResults(Vec<SearchResult>),
+    SeriesRules(Vec<SeriesRule>),
 }
 
 impl XTVResponse {
+    pub fn airings(&self) -> Vec<Airing> {
+        if let XTVResponse::Airings(air, _) = self { air.to_vec() } else { panic!("Not airings!") }
+    }
+    pub fn skipped_airings(&self) -> usize {
+        if let XTVResponse::Airings(_, skipped) = self { *skipped } else { panic!("Not airings!") }
+    }
     pub fn channels(&self) -> Vec<Channel> {
         if let XTVResponse::Channels(chan) = self { chan.to_vec() } else { panic!("Not channels!") }
     }
     pub fn devices(&self) -> Vec<Device> {
         if let XTVResponse::Devices(dev) = self { dev.to_vec() } else { panic!("Not devices!") }
     }
+    pub fn device_state(&self) -> DeviceState {
+        if let XTVResponse::DeviceState(state) = self { state.clone() } else { panic!("Not device state!") }
+    }
+    pub fn dvr_usage(&self) -> DvrUsage {
+        if let XTVResponse::DvrUsage(usage) = self { usage.clone() } else { panic!("Not DVR usage!") }
+    }
     pub fn recordings(&self) -> Vec<Recording> {
         if let XTVResponse::Recordings(rec) = self { rec.to_vec() } else { panic!("Not recordings!") }
     }    
+    pub fn scheduled_recordings(&self) -> Vec<ScheduledRecording> {
+        if let XTVResponse::ScheduledRecordings(rec) = self { rec.to_vec() } else { panic!("Not scheduled recordings!") }
+    }
     pub fn search_results(&self) -> Vec<SearchResult> {
         if let XTVResponse::SearchResults(res) = self { res.to_vec() } else { panic!("Not search results!") }
     }
+    pub fn series_rules(&self) -> Vec<SeriesRule> {
+        if let XTVResponse::SeriesRules(rules) = self { rules.to_vec() } else { panic!("Not series rules!") }
+    }
     fn from_value<C: Fn(T) -> Self, T: DeserializeOwned>(value: serde_json::Value, cons: C) -> Self {
         cons(serde_json::from_value::<T>(value).unwrap())
     }
@@ -61,10 +91,23 @@ impl<'de> Deserialize<'de> for XTVResponse {
                 let value = super::serde::to_map::<M>(access)?;
 
                 match value["_type"].as_str().unwrap() {
+                    "Dvr/Usage" => Ok(XTVResponse::from_value(serde_json::Value::Object(value.clone()), XTVResponse::DvrUsage)),
                     "Enumeration/ChannelMap" => Ok(XTVResponse::from_value(value["_embedded"]["channels"].clone(), XTVResponse::Channels)),
                     "Enumeration/Device" => Ok(XTVResponse::from_value(value["_embedded"]["devices"].clone(), XTVResponse::Devices)),
+                    "Device/State" => Ok(XTVResponse::from_value(serde_json::Value::Object(value.clone()), XTVResponse::DeviceState)),
+                    "Enumeration/Listing" => {
+                        let listings = value["_embedded"]["listings"].as_array().cloned().unwrap_or_default();
+                        let total = listings.len();
+                        let airings = listings.into_iter()
+                            .filter_map(|l| serde_json::from_value::<Airing>(l).ok())
+                            .collect::<Vec<Airing>>();
+                        let skipped = total - airings.len();
+                        Ok(XTVResponse::Airings(airings, skipped))
+                    },
                     "Enumeration/Recording" => Ok(XTVResponse::from_value(value["_embedded"]["recordings"].clone(), XTVResponse::Recordings)),
+                    "Enumeration/ScheduledRecording" => Ok(XTVResponse::from_value(value["_embedded"]["recordings"].clone(), XTVResponse::ScheduledRecordings)),
                     "Enumeration/SearchResult" => Ok(XTVResponse::from_value(value["_embedded"]["results"].clone(), XTVResponse::SearchResults)),
+                    "Enumeration/SeriesRule" => Ok(XTVResponse::from_value(value["_embedded"]["rules"].clone(), XTVResponse::SeriesRules)),
                     _ => Err(M::Error::custom("Unknown type"))
                 }
             }
@@ -0,0 +1,193 @@
+use std::{
+    collections::HashMap,
+    fmt
+};
+use chrono::{
+    DateTime,
+    Duration,
+    offset::Local
+};
+use derive_getters::Getters;
+use ::serde::{
+    Deserialize,
+    Serialize
+};
+use super::recordings::Recording;
+
+
+#[derive(Clone,Debug,Deserialize,PartialEq,Serialize)]
+#[serde(tag = "policy", rename_all = "snake_case")]
+pub enum RetentionPolicy {
+    KeepLast {
+        #[serde(default)]
+        series: Option<String>,
+        count: usize
+    },
+    DeleteWatched {
+        after_days: i64
+    },
+    MaxAge {
+        pattern: String,
+        days: i64
+    }
+}
+
+impl RetentionPolicy {
+    fn expired(&self, recordings: &[Recording], now: &DateTime<Local>) -> Vec<Recording> {
+        match self {
+            RetentionPolicy::KeepLast { series, count } => {
+                let series = series.as_ref().map(|s| s.to_lowercase());
+                let mut by_series = HashMap::<String, Vec<&Recording>>::new();
+                for rec in recordings {
+                    let title = rec.series_title().to_lowercase();
+                    if series.as_ref().is_none_or(|s| title.contains(s)) {
+                        by_series.entry(title).or_default().push(rec);
+                    }
+                }
+                by_series.into_values()
+                    .flat_map(|mut recs| {
+                        recs.sort_by_key(|rec| std::cmp::Reverse(*rec.date_recorded()));
+                        recs.into_iter().skip(*count)
+                    })
+                    .cloned()
+                    .collect()
+            },
+            RetentionPolicy::DeleteWatched { after_days } => recordings.iter()
+                .filter(|rec| *rec.watched() && *rec.date_recorded() < *now - Duration::days(*after_days))
+                .cloned()
+                .collect(),
+            RetentionPolicy::MaxAge { pattern, days } => {
+                let pattern = pattern.to_lowercase();
+                recordings.iter()
+                    .filter(|rec| rec.title().to_lowercase().contains(&pattern) || rec.series_title().to_lowercase().contains(&pattern))
+                    .filter(|rec| *rec.date_recorded() < *now - Duration::days(*days))
+                    .cloned()
+                    .collect()
+            }
+        }
+    }
+}
+
+impl fmt::Display for RetentionPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RetentionPolicy::KeepLast { series: Some(series), count } => write!(f, "keep last {} of \"{}\"", count, series),
+            RetentionPolicy::KeepLast { series: None, count } => write!(f, "keep last {} per series", count),
+            RetentionPolicy::DeleteWatched { after_days } => write!(f, "delete watched after {} days", after_days),
+            RetentionPolicy::MaxAge { pattern, days } => write!(f, "max age {} days for \"{}\"", days, pattern)
+        }
+    }
+}
+
+#[derive(Clone,Debug,Getters)]
+pub struct PruneCandidate {
+    recording: Recording,
+    policy: RetentionPolicy
+}
+
+pub fn plan(policies: &[RetentionPolicy], recordings: &[Recording], now: &DateTime<Local>) -> Vec<PruneCandidate> {
+    let mut candidates = Vec::<PruneCandidate>::new();
+    for policy in policies {
+        for recording in policy.expired(recordings, now) {
+            if !candidates.iter().any(|c| c.recording.media_id() == recording.media_id()) {
+                candidates.push(PruneCandidate { recording, policy: policy.clone() });
+            }
+        }
+    }
+    candidates.sort_by_key(|c| *c.recording.date_recorded());
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recording(id: &str, title: &str, series: Option<&str>, date: &str, watched: bool) -> Recording {
+        serde_json::from_value(serde_json::json!({
+            "title": title,
+            "dateRecorded": date,
+            "mediaId": id,
+            "seriesTitle": series,
+            "isWatched": watched
+        })).unwrap()
+    }
+
+    fn recordings() -> Vec<Recording> {
+        vec![
+            recording("n1", "Nightly News", None, "Thu, 1 Oct 2026 18:30:00 UTC", false),
+            recording("n2", "Nightly News", None, "Fri, 2 Oct 2026 18:30:00 UTC", true),
+            recording("n3", "Nightly News", None, "Sat, 3 Oct 2026 18:30:00 UTC", false),
+            recording("j1", "Jeopardy!", Some("Jeopardy!"), "Thu, 1 Oct 2026 19:00:00 UTC", true),
+            recording("j2", "Jeopardy!", Some("Jeopardy!"), "Fri, 2 Oct 2026 19:00:00 UTC", false),
+            recording("m1", "Heat", None, "Thu, 1 Oct 2026 21:00:00 UTC", false)
+        ]
+    }
+
+    fn media_ids(candidates: &[PruneCandidate]) -> Vec<&str> {
+        let mut ids = candidates.iter().map(|c| c.recording().media_id().as_str()).collect::<Vec<&str>>();
+        ids.sort();
+        ids
+    }
+
+    fn at(recording: &Recording, offset: Duration) -> DateTime<Local> {
+        *recording.date_recorded() + offset
+    }
+
+    #[test]
+    fn keep_last_per_series() {
+        let recordings = recordings();
+        let policies = [RetentionPolicy::KeepLast { series: None, count: 1 }];
+        assert_eq!(media_ids(&plan(&policies, &recordings, &Local::now())), vec!["j1", "n1", "n2"]);
+    }
+
+    #[test]
+    fn keep_last_for_one_series() {
+        let recordings = recordings();
+        let policies = [RetentionPolicy::KeepLast { series: Some("news".to_string()), count: 2 }];
+        assert_eq!(media_ids(&plan(&policies, &recordings, &Local::now())), vec!["n1"]);
+    }
+
+    #[test]
+    fn delete_watched_boundary() {
+        let recordings = recordings();
+        let policies = [RetentionPolicy::DeleteWatched { after_days: 7 }];
+
+        let now = at(&recordings[1], Duration::days(7));
+        assert_eq!(media_ids(&plan(&policies, &recordings, &now)), vec!["j1"]);
+
+        let now = at(&recordings[1], Duration::days(7) + Duration::seconds(1));
+        assert_eq!(media_ids(&plan(&policies, &recordings, &now)), vec!["j1", "n2"]);
+    }
+
+    #[test]
+    fn max_age_boundary() {
+        let recordings = recordings();
+        let policies = [RetentionPolicy::MaxAge { pattern: "NEWS".to_string(), days: 30 }];
+
+        let now = at(&recordings[0], Duration::days(30));
+        assert!(plan(&policies, &recordings, &now).is_empty());
+
+        let now = at(&recordings[0], Duration::days(30) + Duration::seconds(1));
+        assert_eq!(media_ids(&plan(&policies, &recordings, &now)), vec!["n1"]);
+    }
+
+    #[test]
+    fn first_matching_policy_wins() {
+        let recordings = recordings();
+        let policies = [
+            RetentionPolicy::MaxAge { pattern: "news".to_string(), days: 1 },
+            RetentionPolicy::KeepLast { series: None, count: 1 },
+            RetentionPolicy::DeleteWatched { after_days: 0 }
+        ];
+        let candidates = plan(&policies, &recordings, &at(&recordings[2], Duration::days(2)));
+
+        assert_eq!(media_ids(&candidates), vec!["j1", "n1", "n2", "n3"]);
+        for candidate in &candidates {
+            let expected = match candidate.recording().media_id().as_str() {
+                "j1" => &policies[1],
+                _ => &policies[0]
+            };
+            assert_eq!(candidate.policy(), expected);
+        }
+    }
+}
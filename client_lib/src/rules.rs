@@ -0,0 +1,92 @@
+use std::fmt;
+use clap::ValueEnum;
+use derive_getters::Getters;
+use ::serde::{
+    Deserialize,
+    Serialize
+};
+
+
+#[derive(Clone,Copy,Debug,Deserialize,PartialEq,Serialize,ValueEnum)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EpisodeScope {
+    New,
+    All
+}
+
+impl fmt::Display for EpisodeScope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Clone,Debug,Deserialize,Getters,Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesRule {
+    #[serde(default)]
+    id: String,
+    merlin_id: u64,
+    #[serde(default)]
+    title: String,
+    episodes: EpisodeScope,
+    #[serde(default)]
+    channel_number: Option<u16>,
+    #[serde(default)]
+    keep_at_most: Option<u32>,
+    #[serde(default)]
+    start_padding: u32,
+    #[serde(default)]
+    end_padding: u32
+}
+
+impl SeriesRule {
+    pub fn new(merlin_id: u64) -> SeriesRule {
+        SeriesRule {
+            id: String::new(),
+            merlin_id,
+            title: String::new(),
+            episodes: EpisodeScope::New,
+            channel_number: None,
+            keep_at_most: None,
+            start_padding: 0,
+            end_padding: 0
+        }
+    }
+
+    pub fn with_episodes(mut self, episodes: EpisodeScope) -> Self {
+        self.episodes = episodes;
+        self
+    }
+
+    pub fn with_channel(mut self, channel_number: Option<u16>) -> Self {
+        self.channel_number = channel_number;
+        self
+    }
+
+    pub fn with_keep_at_most(mut self, keep_at_most: Option<u32>) -> Self {
+        self.keep_at_most = keep_at_most;
+        self
+    }
+
+    pub fn with_padding(mut self, start_padding: u32, end_padding: u32) -> Self {
+        self.start_padding = start_padding;
+        self.end_padding = end_padding;
+        self
+    }
+
+    pub fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = vec![
+            ("merlinId", self.merlin_id.to_string()),
+            ("episodes", self.episodes.to_string().to_uppercase()),
+            ("startPadding", self.start_padding.to_string()),
+            ("endPadding", self.end_padding.to_string())
+        ];
+        if let Some(channel_number) = self.channel_number {
+            params.push(("channelNumber", channel_number.to_string()));
+        }
+        if let Some(keep_at_most) = self.keep_at_most {
+            params.push(("keepAtMost", keep_at_most.to_string()));
+        }
+        params
+    }
+}
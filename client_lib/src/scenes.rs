@@ -0,0 +1,57 @@
+use std::fmt;
+use derive_getters::Getters;
+use ::serde::{
+    Deserialize,
+    Serialize
+};
+use super::{
+    KeyCode,
+    TuningTarget
+};
+use super::devices::Device;
+
+
+#[derive(Clone,Debug,Deserialize,Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum SceneAction {
+    Tune { target: TuningTarget, id: String },
+    Key { key: KeyCode },
+    Macro { name: String }
+}
+
+impl fmt::Display for SceneAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneAction::Tune { target, id } => write!(f, "tune {} {}", target.to_string().to_lowercase(), id),
+            SceneAction::Key { key } => write!(f, "key {}", key),
+            SceneAction::Macro { name } => write!(f, "macro {}", name)
+        }
+    }
+}
+
+#[derive(Clone,Debug,Deserialize,Getters,Serialize)]
+pub struct SceneStep {
+    device: String,
+    #[serde(flatten)]
+    action: SceneAction
+}
+
+#[derive(Clone,Debug,Deserialize,Getters,Serialize)]
+pub struct Scene {
+    #[serde(default)]
+    description: Option<String>,
+    steps: Vec<SceneStep>
+}
+
+#[derive(Getters)]
+pub struct SceneOutcome {
+    device: Device,
+    action: SceneAction,
+    result: Result<(), Box<dyn std::error::Error>>
+}
+
+impl SceneOutcome {
+    pub fn new(device: Device, action: SceneAction, result: Result<(), Box<dyn std::error::Error>>) -> SceneOutcome {
+        SceneOutcome { device, action, result }
+    }
+}
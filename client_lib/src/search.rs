@@ -1,4 +1,5 @@
 use std::fmt;
+use clap::ValueEnum;
 use derive_getters::Getters;
 use ::serde::{
     Deserialize,
@@ -7,23 +8,99 @@ use ::serde::{
         Error,
         MapAccess,
         Visitor,
-    }
+    },
+    Serialize
 };
+use super::utils::normalize;
 
 
-#[derive(Clone,Getters)]
+#[derive(Clone,Getters,Serialize)]
 pub struct SearchResult {
     name: String,
     subtitle: String,
     entity: Entity,
 }
 
-#[derive(Clone,Debug,Deserialize,Getters)]
-#[serde(rename_all = "camelCase")]
+#[derive(Clone,Copy,Debug,PartialEq,Serialize,ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityType {
+    Movie,
+    Series,
+    Episode,
+    Person,
+    SportsEvent,
+    Network,
+    Other
+}
+
+impl EntityType {
+    fn parse(s: &str) -> EntityType {
+        let s = normalize(s.rsplit('/').next().unwrap_or(s));
+        match s.as_str() {
+            "movie" | "film" => EntityType::Movie,
+            "series" | "tvseries" | "show" => EntityType::Series,
+            "episode" | "tvepisode" => EntityType::Episode,
+            "person" | "credit" => EntityType::Person,
+            "sportsevent" | "sportingevent" | "sports" | "game" => EntityType::SportsEvent,
+            "network" | "station" | "channel" => EntityType::Network,
+            _ => EntityType::Other
+        }
+    }
+}
+
+impl fmt::Display for EntityType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Clone,Debug,Deserialize,Getters,Serialize)]
+pub struct Image {
+    url: String,
+    #[serde(default)]
+    width: Option<u32>,
+    #[serde(default)]
+    height: Option<u32>,
+    #[serde(default, rename = "type")]
+    kind: Option<String>
+}
+
+#[derive(Clone,Debug,Getters,Serialize)]
 pub struct Entity {
     merlin_id: u64,
     name: String,
-    description: String
+    description: String,
+    entity_type: EntityType,
+    release_year: Option<u16>,
+    rating: Option<String>,
+    images: Vec<Image>
+}
+
+impl<'de> Deserialize<'de> for Entity {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value: serde_json::Value = Deserialize::deserialize(deserializer)?;
+
+        Ok(
+            Self {
+                merlin_id: value["merlinId"].as_u64().ok_or(D::Error::missing_field("merlinId"))?,
+                name: value["name"].as_str().ok_or(D::Error::missing_field("name"))?.to_string(),
+                description: value["description"].as_str().unwrap_or_default().to_string(),
+                entity_type: value["entityType"].as_str()
+                    .or(value["_type"].as_str())
+                    .map_or(EntityType::Other, EntityType::parse),
+                release_year: value["releaseYear"].as_u64().map(|year| year as u16),
+                rating: value["rating"].as_str()
+                    .or(value["ratings"][0]["value"].as_str())
+                    .map(str::to_string),
+                images: value["images"].as_array()
+                    .map(|images| images.iter().filter_map(|i| serde_json::from_value(i.clone()).ok()).collect())
+                    .unwrap_or_default()
+            }
+        )
+    }
 }
 
 impl fmt::Display for Entity {
@@ -8,7 +8,7 @@ use actix_web::{
 use parking_lot::Mutex;
 
 
-pub fn run<F: Fn(&mut web::ServiceConfig) -> () + Send + Clone + 'static>(addrs: impl net::ToSocketAddrs, cfg: F) -> Result<Server, Box<dyn std::error::Error>> {
+pub fn run<F: Fn(&mut web::ServiceConfig) + Send + Clone + 'static>(addrs: impl net::ToSocketAddrs, cfg: F) -> Result<Server, Box<dyn std::error::Error>> {
     let stop_handle = web::Data::new(StopHandle::default());
 
     let server = HttpServer::new({
@@ -40,6 +40,6 @@ impl StopHandle {
     }
 
     pub fn stop(&self, graceful: bool) {
-        let _ = self.inner.lock().as_ref().unwrap().stop(graceful);
+        drop(self.inner.lock().as_ref().unwrap().stop(graceful));
     }
 }
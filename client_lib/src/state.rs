@@ -0,0 +1,105 @@
+use std::fmt;
+use derive_getters::Getters;
+use ::serde::{
+    Deserialize,
+    Deserializer,
+    Serialize
+};
+use super::TuningTarget;
+
+
+#[derive(Clone,Copy,Debug,PartialEq,Serialize)]
+pub enum PlaybackState {
+    Playing,
+    Paused,
+    Stopped,
+    Off
+}
+
+impl fmt::Display for PlaybackState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Clone,Debug,Getters,PartialEq,Serialize)]
+pub struct DeviceState {
+    playback: PlaybackState,
+    media_type: Option<TuningTarget>,
+    media_id: Option<String>,
+    title: Option<String>,
+    channel_number: Option<u16>,
+    call_sign: Option<String>,
+    position: Option<u64>,
+    duration: Option<u64>
+}
+
+impl DeviceState {
+    pub fn is_on(&self) -> bool {
+        self.playback != PlaybackState::Off
+    }
+}
+
+impl<'de> Deserialize<'de> for DeviceState {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value: serde_json::Value = Deserialize::deserialize(deserializer)?;
+        let media = &value["_embedded"]["media"];
+
+        let playback = match (value["powerState"].as_str(), value["playbackState"].as_str()) {
+            (Some("OFF"), _) | (Some("STANDBY"), _) => PlaybackState::Off,
+            (_, Some("PLAYING")) => PlaybackState::Playing,
+            (_, Some("PAUSED")) => PlaybackState::Paused,
+            _ => PlaybackState::Stopped
+        };
+
+        let media_type = match media["mediaType"].as_str() {
+            Some("channel") => Some(TuningTarget::Channel),
+            Some("recording") => Some(TuningTarget::Recording),
+            Some("vod") => Some(TuningTarget::VOD),
+            _ => None
+        };
+
+        Ok(
+            Self {
+                playback,
+                media_type,
+                media_id: media["mediaId"].as_str().map(str::to_string),
+                title: media["title"].as_str().map(str::to_string),
+                channel_number: media["channel"]["number"].as_u64().map(|n| n as u16),
+                call_sign: media["channel"]["callSign"].as_str().map(str::to_uppercase),
+                position: value["position"].as_u64(),
+                duration: media["duration"].as_u64()
+            }
+        )
+    }
+}
+
+impl fmt::Display for DeviceState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.playback)?;
+        if let Some(call_sign) = &self.call_sign {
+            write!(f, " {}", call_sign)?;
+        }
+        if let Some(number) = self.channel_number {
+            write!(f, " ({})", number)?;
+        }
+        if let Some(title) = &self.title {
+            write!(f, " {}", title)?;
+        }
+        if let Some(position) = self.position {
+            write!(f, " [{}", hms(position))?;
+            if let Some(duration) = self.duration {
+                write!(f, "/{}", hms(duration))?;
+            }
+            write!(f, "]")?;
+        }
+        Ok(())
+    }
+}
+
+fn hms(seconds: u64) -> String {
+    format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
@@ -0,0 +1,86 @@
+use std::time::Duration;
+use tokio::time::Instant;
+
+
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum SurfCommand {
+    Pause,
+    Resume,
+    Toggle,
+    Stop
+}
+
+#[derive(Clone,Debug)]
+pub enum SurfSource {
+    Favorites,
+    Group(String),
+    Channels(Vec<String>)
+}
+
+#[derive(Clone,Debug)]
+pub struct Surf {
+    channels: Vec<u16>,
+    period: Duration,
+    index: usize,
+    paused: bool,
+    next_at: Option<Instant>
+}
+
+impl Surf {
+    pub fn new(channels: Vec<u16>, period: Duration) -> Result<Surf, Box<dyn std::error::Error>> {
+        if channels.is_empty() {
+            Err("No channels to surf")?
+        }
+        if period.is_zero() {
+            Err("Surf interval must be greater than zero")?
+        }
+        Ok(Surf { channels, period, index: 0, paused: false, next_at: None })
+    }
+
+    pub fn channels(&self) -> &Vec<u16> {
+        &self.channels
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        if self.paused {
+            self.paused = false;
+            self.next_at = Some(Instant::now() + self.period);
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        if self.paused { self.resume() } else { self.pause() }
+    }
+
+    pub fn apply(&mut self, command: SurfCommand) {
+        match command {
+            SurfCommand::Pause => self.pause(),
+            SurfCommand::Resume => self.resume(),
+            SurfCommand::Toggle => self.toggle(),
+            SurfCommand::Stop => self.pause()
+        }
+    }
+
+    pub fn is_due(&self) -> bool {
+        !self.paused && self.next_at.is_none_or(|at| at <= Instant::now())
+    }
+
+    pub fn until_due(&self) -> Duration {
+        self.next_at.map_or(Duration::ZERO, |at| at.saturating_duration_since(Instant::now()))
+    }
+
+    pub fn advance(&mut self) -> u16 {
+        let number = self.channels[self.index];
+        self.index = (self.index + 1) % self.channels.len();
+        self.next_at = Some(Instant::now() + self.period);
+        number
+    }
+}
@@ -1,8 +1,12 @@
-use std::io;
+use std::{
+    io,
+    time::{Duration, Instant}
+};
 use tui::{
     Frame,
     backend::{Backend, CrosstermBackend},
-    widgets::{Block, Borders},
+    layout::{Constraint, Direction, Layout},
+    widgets::{Block, Borders, Paragraph},
     Terminal
 };
 use crossterm::{
@@ -10,26 +14,47 @@ use crossterm::{
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen}
 };
+use clap::Parser;
 use client_lib::{
+    Device,
+    DeviceState,
     KeyCode as XTVKeyCode,
+    Surf,
+    SurfSource,
     XTVClient
 };
 
+const STATUS_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Parser)]
+#[clap(author, version, about, long_about = None)]
+struct Cli {
+    #[clap(long, env = "XTV_DEVICE")]
+    device: Option<String>,
+
+    #[clap(long, default_value_t = 30, value_parser = clap::value_parser!(u64).range(1..))]
+    surf_interval: u64
+}
+
 type CrosstermTerminal = Terminal<CrosstermBackend<std::io::Stdout>>;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+    let client = XTVClient::new()?;
+    let device = client.select_device(cli.device.as_deref()).await?;
+
     let mut terminal = create_terminal()?;
-    run_app(&mut terminal).await?;
+    let result = run_app(&mut terminal, &client, &device, Duration::from_secs(cli.surf_interval)).await;
     shutdown(&mut terminal)?;
-    Ok(())
+    result
 }
 
 fn create_terminal() -> Result<CrosstermTerminal, Box<dyn std::error::Error>> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    return Ok(Terminal::new(CrosstermBackend::new(stdout))?);
+    Ok(Terminal::new(CrosstermBackend::new(stdout))?)
 }
 
 fn shutdown(terminal: &mut CrosstermTerminal) -> Result<(), Box<dyn std::error::Error>> {
@@ -43,29 +68,78 @@ fn shutdown(terminal: &mut CrosstermTerminal) -> Result<(), Box<dyn std::error::
     Ok(())
 }
 
-async fn run_app(terminal: &mut CrosstermTerminal) -> Result<(), Box<dyn std::error::Error>> {
-    let client = XTVClient::new()?;
-    let device = client.lookup_device("Media Room").await?;
+async fn run_app(terminal: &mut CrosstermTerminal, client: &XTVClient, device: &Device, surf_interval: Duration) -> Result<(), Box<dyn std::error::Error>> {
+    let mut state = None;
+    let mut last_status = None::<Instant>;
+    let mut surf = None::<Surf>;
+    let mut error = None::<String>;
 
     loop {
-        terminal.draw(ui)?;
-
-        if let Event::Key(key) = event::read()? {
-            match key.code {
-                KeyCode::Char('q') => { return Ok(()); }
-                KeyCode::Char(' ') => { client.press_key(XTVKeyCode::Pause, &device).await?; }
-                _ => {}
-            }            
+        if let Some(surf) = surf.as_mut() {
+            match client.surf_step(surf, device).await {
+                Ok(Some(_)) => { last_status = None; error = None; }
+                Ok(None) => {}
+                Err(e) => { error = Some(e.to_string()); }
+            }
+        }
+
+        if last_status.is_none_or(|t| t.elapsed() >= STATUS_INTERVAL) {
+            state = client.device_state(device).await.ok();
+            last_status = Some(Instant::now());
+        }
+
+        terminal.draw(|f| ui(f, device, state.as_ref(), surf.as_ref(), error.as_deref()))?;
+
+        if event::poll(Duration::from_millis(250))? {
+            if let Event::Key(key) = event::read()? {
+                match key.code {
+                    KeyCode::Char('q') => { return Ok(()); }
+                    KeyCode::Char(' ') => {
+                        error = client.press_key(XTVKeyCode::Pause, device).await.err().map(|e| e.to_string());
+                        last_status = None;
+                    }
+                    KeyCode::Char('s') => match surf.as_mut() {
+                        Some(surf) => surf.toggle(),
+                        None => {
+                            let started = client.surf_channels(&SurfSource::Favorites).await
+                                .and_then(|channels| Surf::new(channels, surf_interval));
+                            match started {
+                                Ok(started) => { surf = Some(started); error = None; }
+                                Err(e) => { error = Some(e.to_string()); }
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
         }
     }
 }
 
-fn ui<B: Backend>(f: &mut Frame<B>) {
+fn ui<B: Backend>(f: &mut Frame<B>, device: &Device, state: Option<&DeviceState>, surf: Option<&Surf>, error: Option<&str>) {
     let size = f.size();
 
     let block = Block::default()
         .title("XTV")
         .borders(Borders::ALL);
 
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .split(size);
+
+    let status = Paragraph::new(match (error, state) {
+        (Some(error), _) => format!("Error: {}", error),
+        (None, Some(state)) => state.to_string(),
+        (None, None) => "Unknown".to_string()
+    })
+    .block(Block::default().title(match surf {
+        Some(surf) if surf.is_paused() => format!("{} [surf paused]", device.name()),
+        Some(_) => format!("{} [surfing]", device.name()),
+        None => device.name().to_string()
+    }).borders(Borders::ALL));
+
     f.render_widget(block, size);
+    f.render_widget(status, chunks[0]);
 }